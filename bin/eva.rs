@@ -1,6 +1,8 @@
-use std::{collections::HashSet, env, fs::read_to_string};
+use std::{collections::HashSet, env, fs::read_to_string, path::Path};
 
-use essential_protein::eps::evaluation::{evalustion, pr, roc, top_n};
+use essential_protein::eps::evaluation::{
+    evalustion, jackknife_curves, pr, ranking_metrics, roc, top_n, write_jackknife,
+};
 
 fn read_essential_protein() -> HashSet<String> {
     let mut essentil_proteins = HashSet::<String>::new();
@@ -25,22 +27,61 @@ fn read_pre_file(pre_file: &str) -> Vec<String> {
     res
 }
 
+const USAGE: &str = "Usage: eva <prediction file>... [--top=<k>]";
+
 fn main() {
-    let arags = env::args().collect::<Vec<String>>();
+    let arags = env::args().skip(1).collect::<Vec<String>>();
+    // jackknife 曲线的最大排名，默认导出完整排名
+    let top = arags
+        .iter()
+        .find_map(|a| a.strip_prefix("--top="))
+        .map_or(usize::MAX, |k| k.parse().expect("Failed to parse --top!"));
+    let files = arags
+        .iter()
+        .filter(|a| !a.starts_with("--top="))
+        .collect::<Vec<_>>();
+    if files.is_empty() {
+        eprintln!("{}", USAGE);
+        std::process::exit(1);
+    }
 
     let refprotein = read_essential_protein();
-    let preprotein = read_pre_file(arags[1].as_str());
-
-    // 真是有的关键蛋白质
-    let refprotein = refprotein
-        .intersection(&preprotein.clone().into_iter().collect())
-        .map(|s| s.clone())
-        .collect::<HashSet<_>>();
-
-    evalustion(&refprotein, &preprotein);
-    top_n(&refprotein, &preprotein);
-    let roc = roc(&refprotein, &preprotein, 50);
-    println!("{:?} \n {:?}", roc[0], roc[1]);
-    let pr = pr(&refprotein, &preprotein, 50);
-    println!("{:?} \n {:?}", pr[0], pr[1]);
+    let mut rankings = Vec::new();
+    for file in files {
+        let preprotein = read_pre_file(file.as_str());
+
+        // 真是有的关键蛋白质
+        let refprotein = refprotein
+            .intersection(&preprotein.clone().into_iter().collect())
+            .map(|s| s.clone())
+            .collect::<HashSet<_>>();
+
+        println!("{}", file);
+        evalustion(&refprotein, &preprotein);
+        top_n(&refprotein, &preprotein);
+        let metrics = ranking_metrics(&refprotein, &preprotein, &[]);
+        let format = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.4}", v));
+        println!(
+            "AUROC: {} AUPRC: {}",
+            format(metrics.auroc),
+            format(metrics.auprc)
+        );
+        let roc = roc(&refprotein, &preprotein, 50);
+        println!("{:?} \n {:?}", roc[0], roc[1]);
+        let pr = pr(&refprotein, &preprotein, 50);
+        println!("{:?} \n {:?}", pr[0], pr[1]);
+
+        let method = Path::new(file)
+            .file_stem()
+            .map_or(file.clone(), |s| s.to_string_lossy().to_string());
+        rankings.push((method, preprotein));
+    }
+
+    // 多个方法的 jackknife 曲线，每列一个方法
+    let rankings = rankings
+        .iter()
+        .map(|(m, p)| (m.as_str(), p.as_slice()))
+        .collect::<Vec<_>>();
+    let curves = jackknife_curves(&refprotein, &rankings, top);
+    write_jackknife("result/jackknife.csv", &curves).expect("Failed to write jackknife!");
 }
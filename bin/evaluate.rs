@@ -0,0 +1,52 @@
+use std::{env, path::Path};
+
+use essential_protein::eva::{
+    evaluate_files, read_complexes, read_known_proteins, write_scores, SortBy,
+};
+
+const USAGE: &str = "Usage: evaluate <ppi file> <reference complexes> <prediction dir|glob> [output=result/scores.csv] [sort=f-score|acc]";
+
+// 预测结果文件：目录下的所有文件，或者匹配通配符的文件，按文件名排列
+fn prediction_files(pattern: &str) -> Vec<String> {
+    let pattern = if Path::new(pattern).is_dir() {
+        format!("{}/*", pattern.trim_end_matches(['/', '\\']))
+    } else {
+        pattern.to_string()
+    };
+    let mut files = glob::glob(&pattern)
+        .expect("Failed to parse glob pattern!")
+        .filter_map(|p| p.ok())
+        .filter(|p| p.is_file())
+        .map(|p| p.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+fn main() {
+    let args = env::args().collect::<Vec<String>>();
+    if args.len() < 4 {
+        eprintln!("{}", USAGE);
+        std::process::exit(1);
+    }
+    let output = args.get(4).map_or("result/scores.csv", |s| s.as_str());
+    let sort_by = args
+        .get(5)
+        .map_or(Ok(SortBy::default()), |s| s.parse::<SortBy>())
+        .expect("Failed to parse sort key!");
+
+    let known = read_known_proteins(&args[1]);
+    let refc = read_complexes(&args[2], Some(&known));
+    let files = prediction_files(&args[3]);
+    println!(
+        "{} reference complexes, {} prediction files",
+        refc.len(),
+        files.len()
+    );
+
+    let rows = evaluate_files(&refc, &files, &known, sort_by);
+    for row in rows.iter() {
+        println!("{}\t{}", row.file, row.scores);
+    }
+    write_scores(output, &rows).expect("Failed to write scores!");
+}
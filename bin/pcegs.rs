@@ -1,24 +1,118 @@
-use std::{fs::File, io::Write};
+use std::{env, io::Write, path::Path};
 
 use essential_protein::{
-    dag::weight_by_dag_topo, eva::Complex, gene_expression::get_dpins, graph::Graph, pcegs,
+    coherence::{complex_coherence, write_coherence, CoherenceOptions},
+    consolidate::{consolidate, ConsolidateOptions, ConsolidatedComplex},
+    dag::Dag,
+    enrichment::{
+        enrich_complexes, enriched_fraction, write_enrichment, EnrichmentOptions, TOP_TERMS,
+    },
+    gene_expression::{
+        read_essential_protein, ActivityModel, DpinBuilder, MissingProfile, DEFAULT_EXPRESSION_FILE,
+    },
+    graph::Graph,
+    pcegs::{self, PcegsOptions},
+    significance::{complex_significance, write_significance, SignificanceOptions},
+    tracking::{track_complexes, TrackOptions},
+    writer::{self, OutputFormat, WriterOptions, DEFAULT_COHESION_CUTOFF},
 };
 
+const USAGE: &str = "Usage: pcegs [--output=result/krogan_core.txt] [--format=membership|long|json|csv] [--cutoff=0.2] [--significant]";
+
+// 形如 --name=value 的命令行参数
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .find_map(|a| a.strip_prefix(name)?.strip_prefix('='))
+}
+
 fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let known = ["--output", "--format", "--cutoff", "--significant"];
+    if let Some(arg) = args
+        .iter()
+        .find(|a| !known.contains(&a.split('=').next().unwrap()))
+    {
+        eprintln!("unknown argument: {}\n{}", arg, USAGE);
+        std::process::exit(1);
+    }
+    // 复合物的输出文件，其余结果文件以去掉后缀的路径为前缀
+    let output = flag(&args, "--output").unwrap_or("result/krogan_core.txt");
+    let prefix = Path::new(output).with_extension("");
+    let prefix = prefix.to_string_lossy();
+    // 未指定格式时根据后缀推断
+    let writer_options = WriterOptions {
+        format: flag(&args, "--format")
+            .map(|f| f.parse().expect("Failed to parse --format!"))
+            .or_else(|| OutputFormat::from_path(output))
+            .unwrap_or(OutputFormat::Membership),
+        cohesion_cutoff: flag(&args, "--cutoff").map_or(DEFAULT_COHESION_CUTOFF, |c| {
+            c.parse().expect("Failed to parse --cutoff!")
+        }),
+    };
+
     let graph = Graph::new_from_file("./data/krogan_core/krogan_core.txt", false);
     // 动态网络
-    let dpins = get_dpins(&graph);
-
-    let mut complexes = Vec::new();
-    for mut dp in dpins.into_iter() {
-        // weight_by_dag(&mut dp);
-        weight_by_dag_topo(&mut dp, 0.5);
-        let res = pcegs::pcegs(&dp, 0.4);
-        // res.into_iter().for_each(|c| println!("{}", c));
-        complexes.extend(res);
-    }
-    let file = format!("result/krogan_core.txt");
-    write_file(file, complexes);
+    let options = PcegsOptions {
+        parallel: true,
+        ..PcegsOptions::new(0.5, 0.4)
+    };
+    let dpins = DpinBuilder::new(DEFAULT_EXPRESSION_FILE)
+        .essential(read_essential_protein())
+        .model(ActivityModel::Edpin)
+        .missing(MissingProfile::KeepAll)
+        .build_dpins(&graph);
+    let (labels, dpins): (Vec<_>, Vec<_>) = dpins.into_iter().map(|d| (d.label, d.graph)).unzip();
+    let (per_time, provenance) = pcegs::pcegs_dpins_with(dpins, &options);
+    // 复合物在相邻时间点之间的变化
+    let tracking = track_complexes(&per_time, &TrackOptions::default());
+    tracking
+        .write_timeline(&format!("{}_timeline.tsv", prefix), &per_time, &labels)
+        .expect("Failed to write timeline!");
+    // 合并不同时间点识别出的相同复合物
+    let consolidated = consolidate(&graph, &per_time, &ConsolidateOptions::default());
+    write_time_points(&format!("{}_time_points.txt", prefix), &consolidated);
+    // 复合物的显著性，传入 --significant 时只输出显著的复合物
+    let complexes = consolidated
+        .iter()
+        .map(|c| c.complex.clone())
+        .collect::<Vec<_>>();
+    let significance_options = SignificanceOptions::default();
+    let significance = complex_significance(&graph, &complexes, &significance_options);
+    write_significance(&format!("{}.significance.tsv", prefix), &significance)
+        .expect("Failed to write significance!");
+    // GO 富集分析，以网络中的蛋白质为背景
+    let enrichment_options = EnrichmentOptions::default();
+    let mut dag = Dag::new();
+    let enrichment = enrich_complexes(&dag, &graph, &complexes, &enrichment_options);
+    write_enrichment(
+        &format!("{}.enrichment.tsv", prefix),
+        &enrichment,
+        TOP_TERMS,
+    )
+    .expect("Failed to write enrichment!");
+    println!(
+        "enriched complexes: {:.4}",
+        enriched_fraction(&enrichment, enrichment_options.fdr)
+    );
+    // 功能一致性、共定位和共表达评分
+    let express = DpinBuilder::new(DEFAULT_EXPRESSION_FILE).express(&graph);
+    let coherence = complex_coherence(
+        &mut dag,
+        Some(&express),
+        &complexes,
+        &CoherenceOptions::default(),
+    );
+    write_coherence(&format!("{}.coherence.tsv", prefix), &coherence)
+        .expect("Failed to write coherence!");
+    let only_significant = args.iter().any(|arg| arg == "--significant");
+    let complexes = complexes
+        .iter()
+        .zip(significance.iter())
+        .filter(|(_, s)| !only_significant || s.is_significant(significance_options.alpha))
+        .map(|(c, _)| c);
+    writer::write_complexes(output, complexes, &writer_options)
+        .expect("Failed to write complexes!");
+    pcegs::write_provenance(&format!("{}.provenance.jsonl", prefix), &provenance);
 
     // let mut alpha = 0.;
 
@@ -38,52 +132,24 @@ fn main() {
     //             complexes.extend(res);
     //         }
     //         let file = format!("result/krogan_core_{:.2}_{:.2}.txt", alpha, beta);
-    //         write_file(file, complexes);
+    //         writer::write_complexes(&file, &complexes, &output).unwrap();
     //         beta += 0.05;
     //     }
     //     alpha += 0.05;
     // }
 }
 
-fn write_file(file: String, complexes: Vec<Complex<String>>) {
-    let mut file = File::create(file).expect("Failed to crate faile!");
-    // file.write_all(buf)
-    for complex in complexes {
-        if complex.cohesion.le(&0.2) {
-            continue;
-        }
-        let mut str = format!(
-            "{} {:.4}",
-            complex
-                .proteins
-                .iter()
-                .map(|p| format!("{}", p))
-                .collect::<Vec<String>>()
-                .join("\t"),
-            complex.cohesion
-        );
-        str.push('\n');
-        file.write_all(str.as_bytes())
+// 每行为复合物活跃的时间点及其成员
+fn write_time_points(file: &str, complexes: &[ConsolidatedComplex]) {
+    let mut file = writer::create_file(file).expect("Failed to create file!");
+    for c in complexes {
+        let times = c
+            .time_points
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        writeln!(file, "{}\t{}", times, c.complex.proteins.join("\t"))
             .expect("Failed to write to file!");
     }
 }
-
-#[test]
-fn teste_eva() {
-    let mut commands = String::new();
-
-    let mut a = 0.;
-    while (1.04).ge(&a) {
-        let mut b = 0.;
-        while (1.04).ge(&b) {
-            commands = format!(
-                "{} python evaluation\\match.py -n gavin result\\gavin_{:.2?}_{:.2}.txt \n",
-                commands, a, b
-            );
-            b += 0.05;
-        }
-        a += 0.5;
-    }
-    let mut file = File::create("gavin.ps1").expect("msg");
-    file.write_all(commands.as_bytes()).unwrap();
-}
@@ -0,0 +1,375 @@
+/// 参考复合物目录的读取：CYC2008、MIPS、SGD（GO 细胞组分）和 CORUM
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fs::read_to_string,
+};
+
+use crate::eva::{Complex, MAX_SIZE, MIN_SIZE};
+
+// 带编号和名称的参考复合物
+#[derive(Debug, Clone)]
+pub struct NamedComplex {
+    pub id: String,
+    pub name: String,
+    pub complex: Complex<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CatalogOptions {
+    // 限制在网络中之后的成员数需在 [min_size, max_size] 之间
+    pub min_size: usize,
+    pub max_size: usize,
+}
+
+impl Default for CatalogOptions {
+    fn default() -> Self {
+        Self {
+            min_size: MIN_SIZE,
+            max_size: MAX_SIZE,
+        }
+    }
+}
+
+// CORUM 中作为蛋白质标识的亚基列
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CorumSubunits {
+    #[default]
+    UniProt,
+    Entrez,
+    GeneName,
+}
+
+impl CorumSubunits {
+    // 新旧两种导出格式的列名，统一为小写并去掉空格和下划线后比较
+    fn columns(&self) -> &'static [&'static str] {
+        match self {
+            Self::UniProt => &["subunits(uniprotids)", "subunitsuniprotid"],
+            Self::Entrez => &["subunits(entrezids)", "subunitsentrezid"],
+            Self::GeneName => &["subunits(genename)", "subunitsgenename"],
+        }
+    }
+}
+
+// 拆分复合物的名称和成员，名称可传给评价报告
+pub fn split_names(named: Vec<NamedComplex>) -> (Vec<String>, Vec<Complex<String>>) {
+    named.into_iter().map(|c| (c.name, c.complex)).unzip()
+}
+
+// 每行一个复合物、成员以空白分隔的文件，编号为行号（从1开始）
+pub fn read_plain(
+    file: &str,
+    known: Option<&HashSet<String>>,
+    options: &CatalogOptions,
+) -> Vec<NamedComplex> {
+    let contents = read_to_string(file).expect("Failed to read complex file!");
+    let groups = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let id = (i + 1).to_string();
+            let members = line.split_whitespace().map(|p| p.to_string()).collect();
+            (id.clone(), id, members)
+        })
+        .collect();
+    finish(groups, known, options)
+}
+
+// CYC2008 表格（制表符分隔）：ORF, Name, Complex, ...，每行一个成员，
+// 编号为复合物在文件中出现的顺序（从1开始）
+pub fn read_cyc2008(
+    file: &str,
+    known: Option<&HashSet<String>>,
+    options: &CatalogOptions,
+) -> Vec<NamedComplex> {
+    let contents = read_to_string(file).expect("Failed to read CYC2008 file!");
+    let lines = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>();
+    // 没有表头时按 ORF, Name, Complex 的列顺序读取
+    let header = split_row(lines.first().copied().unwrap_or_default());
+    let (skip, orf, complex) = match column(&header, &["orf"]) {
+        Some(orf) => (1, orf, column(&header, &["complex"]).unwrap_or(2)),
+        None => (0, 0, 2),
+    };
+
+    let mut groups = Vec::<(String, String, Vec<String>)>::new();
+    let mut index = HashMap::<String, usize>::new();
+    for line in lines.into_iter().skip(skip) {
+        let row = split_row(line);
+        let (Some(p), Some(name)) = (row.get(orf), row.get(complex)) else {
+            continue;
+        };
+        let i = *index.entry(name.to_string()).or_insert_with(|| {
+            groups.push(((groups.len() + 1).to_string(), name.to_string(), vec![]));
+            groups.len() - 1
+        });
+        groups[i].2.push(p.to_string());
+    }
+    finish(groups, known, options)
+}
+
+// MIPS 复合物目录：数据文件每行为 ORF|类别编号|...，
+// 名称文件（complexcat_scheme）每行为 类别编号 名称，缺省时名称为类别编号；
+// 550 类别下为高通量实验得到的复合物，需要时可按编号过滤
+pub fn read_mips(
+    data_file: &str,
+    scheme_file: Option<&str>,
+    known: Option<&HashSet<String>>,
+    options: &CatalogOptions,
+) -> Vec<NamedComplex> {
+    let names = scheme_file.map_or(HashMap::new(), |file| {
+        read_to_string(file)
+            .expect("Failed to read MIPS scheme file!")
+            .lines()
+            .filter_map(|line| {
+                let (id, name) = line.trim().split_once(char::is_whitespace)?;
+                Some((id.to_string(), name.trim().to_string()))
+            })
+            .collect::<HashMap<_, _>>()
+    });
+
+    let contents = read_to_string(data_file).expect("Failed to read MIPS complex file!");
+    let mut members = HashMap::<String, Vec<String>>::new();
+    for line in contents.lines() {
+        let mut fields = line.split('|').map(|f| f.trim());
+        let (Some(p), Some(id)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if !p.is_empty() && !id.is_empty() {
+            members
+                .entry(id.to_string())
+                .or_default()
+                .push(p.to_string());
+        }
+    }
+
+    let mut groups = members
+        .into_iter()
+        .map(|(id, ps)| {
+            let name = names.get(&id).cloned().unwrap_or(id.clone());
+            (id, name, ps)
+        })
+        .collect::<Vec<_>>();
+    groups.sort_by(|a, b| a.0.cmp(&b.0));
+    finish(groups, known, options)
+}
+
+// SGD 由 GO 细胞组分注释得到的复合物（go_protein_complex_slim.tab）：
+// 第一列为 "Component: 名称 (GO:编号)"，第二列为以 | 分隔的 基因名/ORF/SGDID/... 成员
+pub fn read_sgd_complexes(
+    file: &str,
+    known: Option<&HashSet<String>>,
+    options: &CatalogOptions,
+) -> Vec<NamedComplex> {
+    let contents = read_to_string(file).expect("Failed to read SGD complex file!");
+    let groups = contents
+        .lines()
+        .filter_map(|line| {
+            let (term, members) = line.split_once('\t')?;
+            let term = term.trim();
+            let term = term.strip_prefix("Component:").unwrap_or(term).trim();
+            let (name, id) = match term.rfind("(GO:") {
+                Some(i) => (term[..i].trim(), term[i + 1..].trim_end_matches(')').trim()),
+                None => (term, term),
+            };
+            let members = members
+                .split('|')
+                .filter_map(|m| {
+                    let fields = m.split('/').map(|f| f.trim()).collect::<Vec<_>>();
+                    // 优先使用 ORF，没有时使用基因名
+                    match fields.get(1).filter(|f| !f.is_empty()) {
+                        Some(orf) => Some(orf.to_string()),
+                        None => fields
+                            .first()
+                            .filter(|f| !f.is_empty())
+                            .map(|f| f.to_string()),
+                    }
+                })
+                .collect();
+            Some((id.to_string(), name.to_string(), members))
+        })
+        .collect();
+    finish(groups, known, options)
+}
+
+// CORUM allComplexes（制表符分隔，带表头），亚基以 ; 分隔；
+// organism 不为 None 时只保留该物种（不区分大小写）的复合物
+pub fn read_corum(
+    file: &str,
+    subunits: CorumSubunits,
+    organism: Option<&str>,
+    known: Option<&HashSet<String>>,
+    options: &CatalogOptions,
+) -> Vec<NamedComplex> {
+    let contents = read_to_string(file).expect("Failed to read CORUM file!");
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+    let header = split_row(lines.next().unwrap_or_default());
+    let id = column(&header, &["complexid"]).expect("CORUM file has no complex id column!");
+    let name = column(&header, &["complexname"]).expect("CORUM file has no complex name column!");
+    let members = column(&header, subunits.columns()).expect("CORUM file has no subunit column!");
+    let species = column(&header, &["organism"]);
+
+    let groups = lines
+        .filter_map(|line| {
+            let row = split_row(line);
+            if let (Some(organism), Some(s)) = (organism, species) {
+                if !row.get(s)?.eq_ignore_ascii_case(organism) {
+                    return None;
+                }
+            }
+            let ps = row
+                .get(members)?
+                .split(';')
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty() && p != "None")
+                .collect();
+            Some((row.get(id)?.to_string(), row.get(name)?.to_string(), ps))
+        })
+        .collect();
+    finish(groups, known, options)
+}
+
+fn split_row(line: &str) -> Vec<&str> {
+    line.split('\t').map(|f| f.trim()).collect()
+}
+
+// 按列名查找列，列名统一为小写并去掉空格和下划线
+fn column(header: &[&str], names: &[&str]) -> Option<usize> {
+    let normalize = |s: &str| s.to_lowercase().replace([' ', '_'], "");
+    header
+        .iter()
+        .position(|h| names.iter().any(|n| normalize(h) == *n))
+}
+
+// 成员统一为大写（与 read_known_proteins 一致），限制在网络中并去重，
+// 按大小过滤，成员完全相同的复合物只保留第一个
+fn finish(
+    groups: Vec<(String, String, Vec<String>)>,
+    known: Option<&HashSet<String>>,
+    options: &CatalogOptions,
+) -> Vec<NamedComplex> {
+    let mut seen = HashSet::new();
+    groups
+        .into_iter()
+        .filter_map(|(id, name, members)| {
+            let members = members
+                .into_iter()
+                .map(|p| p.to_uppercase())
+                .filter(|p| known.is_none_or(|k| k.contains(p)))
+                .collect::<BTreeSet<_>>();
+            if members.len() < options.min_size
+                || members.len() > options.max_size
+                || !seen.insert(members.clone())
+            {
+                return None;
+            }
+            Some(NamedComplex {
+                id,
+                name,
+                complex: Complex::new(members.into_iter().collect(), 0.),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{
+        read_corum, read_cyc2008, read_mips, read_sgd_complexes, split_names, CatalogOptions,
+        CorumSubunits,
+    };
+
+    #[test]
+    fn test_read_catalogs() {
+        let dir = std::env::temp_dir().join(format!("catalog_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, contents: &str| {
+            let file = dir.join(name);
+            std::fs::write(&file, contents).unwrap();
+            file.to_str().unwrap().to_string()
+        };
+        let known = ["YAL001C", "YBR123C", "YDR362C", "YGR047C", "YOR110W"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<HashSet<_>>();
+        let options = CatalogOptions {
+            min_size: 3,
+            max_size: 4,
+        };
+
+        let cyc = write(
+            "cyc2008.tab",
+            "ORF\tName\tComplex\tPubMed_id\n\
+             YAL001C\tTFC3\tTFIIIC complex\t1\n\
+             YBR123C\tTFC1\tTFIIIC complex\t1\n\
+             YDR362C\tTFC6\tTFIIIC complex\t1\n\
+             YGR047C\tTFC4\tOther complex\t1\n\
+             YOR110W\tTFC7\tOther complex\t1\n",
+        );
+        let complexes = read_cyc2008(&cyc, Some(&known), &options);
+        assert_eq!(complexes.len(), 1);
+        assert_eq!(
+            (complexes[0].id.as_str(), complexes[0].name.as_str()),
+            ("1", "TFIIIC complex")
+        );
+        assert_eq!(
+            complexes[0].complex.proteins,
+            vec!["YAL001C", "YBR123C", "YDR362C"]
+        );
+
+        let data = write(
+            "complexcat_data",
+            "YAL001c|510.190.10.20|\nYBR123c|510.190.10.20|\nYDR362c|510.190.10.20|\n\
+             YGR047c|510.190.10.20|\nYOR110w|510.190.10.20|\nYAL001c|550.1.1|\n",
+        );
+        let scheme = write("complexcat_scheme", "510.190.10.20 TFIIIC\n550.1.1 HTP\n");
+        let (names, complexes) = split_names(read_mips(&data, Some(&scheme), None, &options));
+        // 5个成员超过上限，1个成员低于下限
+        assert!(names.is_empty() && complexes.is_empty());
+        let large = CatalogOptions {
+            min_size: 1,
+            max_size: 5,
+        };
+        let (names, complexes) = split_names(read_mips(&data, Some(&scheme), None, &large));
+        assert_eq!(names, vec!["TFIIIC", "HTP"]);
+        assert_eq!(complexes[0].len(), 5);
+
+        let sgd = write(
+            "go_protein_complex_slim.tab",
+            "Component: transcription factor TFIIIC complex (GO:0000127)\t\
+             TFC3/YAL001C/S000000001/Verified|TFC1/YBR123C/S000000327/Verified|\
+             TFC6/YDR362C/S000002770/Verified|UNKNOWN/YXX000W/S0/Dubious\n",
+        );
+        let complexes = read_sgd_complexes(&sgd, Some(&known), &options);
+        assert_eq!(complexes[0].id, "GO:0000127");
+        assert_eq!(complexes[0].name, "transcription factor TFIIIC complex");
+        assert_eq!(complexes[0].complex.len(), 3);
+
+        let corum = write(
+            "allComplexes.txt",
+            "ComplexID\tComplexName\tOrganism\tsubunits(UniProt IDs)\tsubunits(Gene name)\n\
+             1\tBCL6-HDAC4 complex\tHuman\tP41182;P56524;Q9UQL6\tBCL6;HDAC4;HDAC5\n\
+             2\tMouse complex\tMouse\tP1;P2;P3\tA;B;C\n",
+        );
+        let complexes = read_corum(
+            &corum,
+            CorumSubunits::GeneName,
+            Some("human"),
+            None,
+            &options,
+        );
+        assert_eq!(complexes.len(), 1);
+        assert_eq!(
+            complexes[0].complex.proteins,
+            vec!["BCL6", "HDAC4", "HDAC5"]
+        );
+        let complexes = read_corum(&corum, CorumSubunits::UniProt, None, None, &options);
+        assert_eq!(complexes[1].name, "Mouse complex");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
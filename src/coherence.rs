@@ -0,0 +1,227 @@
+/// 复合物的功能一致性、共定位和共表达评分，不依赖参考复合物
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::{self, BufWriter, Write},
+};
+
+use crate::{
+    dag::Dag,
+    eva::Complex,
+    gene_expression::{CoExpression, GeneExpress},
+    writer::create_file,
+};
+
+/// 细胞组分（cellular_component）的根术语
+pub const CELLULAR_COMPONENT: &str = "GO:0005575";
+/// 默认的表达谱相关系数阈值
+pub const CORRELATION_THRESHOLD: f64 = 0.5;
+
+#[derive(Debug, Clone)]
+pub struct CoherenceOptions {
+    pub measure: CoExpression,
+    // 与至少一个其他成员的相关系数不小于该值时视为共表达
+    pub correlation_threshold: f64,
+}
+
+impl Default for CoherenceOptions {
+    fn default() -> Self {
+        Self {
+            measure: CoExpression::Pearson,
+            correlation_threshold: CORRELATION_THRESHOLD,
+        }
+    }
+}
+
+// 有注释或表达谱的成员不足两个时对应的评分为 None
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ComplexCoherence {
+    // 有注释的成员之间 get_function_sim 的平均值
+    pub functional_similarity: Option<f64>,
+    // 有细胞组分注释的成员中，注释到同一细胞组分术语的最大比例
+    pub colocalization: Option<f64>,
+    // 有表达谱的成员中，与其他成员共表达的比例
+    pub coexpression: Option<f64>,
+}
+
+pub fn complex_coherence(
+    dag: &mut Dag,
+    express: Option<&GeneExpress>,
+    complexes: &[Complex<String>],
+    options: &CoherenceOptions,
+) -> Vec<ComplexCoherence> {
+    let root = dag.term_id(CELLULAR_COMPONENT);
+    // 术语是否属于细胞组分
+    let mut is_component = HashMap::<usize, bool>::new();
+
+    complexes
+        .iter()
+        .map(|c| {
+            let members = c.proteins.iter().collect::<BTreeSet<_>>();
+
+            let annotated = members
+                .iter()
+                .filter(|p| dag.annotations(p).is_some_and(|g| !g.is_empty()))
+                .collect::<Vec<_>>();
+            let mut sims = Vec::new();
+            for (i, a) in annotated.iter().enumerate() {
+                for b in annotated.iter().skip(i + 1) {
+                    sims.push(dag.get_function_sim(a, b));
+                }
+            }
+            let functional_similarity = mean(&sims);
+
+            let colocalization = root.and_then(|root| {
+                let mut counts = HashMap::<usize, usize>::new();
+                let mut localized = 0;
+                for p in members.iter() {
+                    let terms = dag
+                        .annotations(p)
+                        .into_iter()
+                        .flatten()
+                        .filter(|t| {
+                            **t != root
+                                && *is_component
+                                    .entry(**t)
+                                    .or_insert_with(|| dag.get_ancestors(**t).contains(&root))
+                        })
+                        .copied()
+                        .collect::<Vec<_>>();
+                    if !terms.is_empty() {
+                        localized += 1;
+                    }
+                    terms
+                        .into_iter()
+                        .for_each(|t| *counts.entry(t).or_default() += 1);
+                }
+                (localized >= 2)
+                    .then(|| counts.values().copied().max().unwrap_or(0) as f64 / localized as f64)
+            });
+
+            let coexpression = express.and_then(|express| {
+                let profiled = members
+                    .iter()
+                    .filter(|a| {
+                        members.iter().any(|b| {
+                            a != &b && express.correlation(a, b, options.measure).is_some()
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let correlated = profiled
+                    .iter()
+                    .filter(|a| {
+                        profiled.iter().any(|b| {
+                            a != &b
+                                && express
+                                    .correlation(a, b, options.measure)
+                                    .is_some_and(|r| r >= options.correlation_threshold)
+                        })
+                    })
+                    .count();
+                (profiled.len() >= 2).then(|| correlated as f64 / profiled.len() as f64)
+            });
+
+            ComplexCoherence {
+                functional_similarity,
+                colocalization,
+                coexpression,
+            }
+        })
+        .collect()
+}
+
+// 各项评分在有值的复合物上的平均值
+pub fn mean_coherence(coherence: &[ComplexCoherence]) -> ComplexCoherence {
+    let average = |f: fn(&ComplexCoherence) -> Option<f64>| {
+        mean(&coherence.iter().filter_map(f).collect::<Vec<_>>())
+    };
+    ComplexCoherence {
+        functional_similarity: average(|c| c.functional_similarity),
+        colocalization: average(|c| c.colocalization),
+        coexpression: average(|c| c.coexpression),
+    }
+}
+
+// 每行一个复合物: complex_id, functional_similarity, colocalization, coexpression，
+// 最后一行为平均值，没有值时为 -
+pub fn write_coherence(file: &str, coherence: &[ComplexCoherence]) -> io::Result<()> {
+    let mut out = BufWriter::new(create_file(file)?);
+    writeln!(
+        out,
+        "complex_id\tfunctional_similarity\tcolocalization\tcoexpression"
+    )?;
+    let format = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.4}", v));
+    let mean = mean_coherence(coherence);
+    let rows = coherence
+        .iter()
+        .enumerate()
+        .map(|(id, c)| (id.to_string(), c))
+        .chain([("mean".to_string(), &mean)]);
+    for (id, c) in rows {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}",
+            id,
+            format(c.functional_similarity),
+            format(c.colocalization),
+            format(c.coexpression)
+        )?;
+    }
+    out.flush()
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    match values.len() {
+        0 => None,
+        n => Some(values.iter().sum::<f64>() / n as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        dag::Dag,
+        eva::{read_complexes, Complex, COMPLEX_REF},
+        gene_expression::{GeneExpress, DEFAULT_EXPRESSION_FILE},
+        graph::Graph,
+    };
+
+    use super::{complex_coherence, mean_coherence};
+
+    #[test]
+    fn test_complex_coherence() {
+        let mut dag = Dag::new();
+        let graph = Graph::new_from_file("./data/collins/collins.txt", false);
+        let known = graph.id_protein.iter().cloned().collect::<HashSet<_>>();
+        let express = GeneExpress::new(DEFAULT_EXPRESSION_FILE, &known);
+        let mut complexes = read_complexes(COMPLEX_REF, Some(&known));
+        complexes.truncate(20);
+        complexes.push(Complex::new(
+            graph
+                .id_protein
+                .iter()
+                .step_by(97)
+                .take(5)
+                .cloned()
+                .collect(),
+            0.,
+        ));
+        complexes.push(Complex::new(vec!["NOT_A_PROTEIN".to_string()], 0.));
+
+        let coherence =
+            complex_coherence(&mut dag, Some(&express), &complexes, &Default::default());
+        assert_eq!(coherence.len(), 22);
+        let reference = mean_coherence(&coherence[..20]);
+        let random = coherence[20];
+        assert!(reference.functional_similarity.unwrap() > random.functional_similarity.unwrap());
+        assert!(reference.colocalization.unwrap() > random.colocalization.unwrap());
+        assert!(coherence[..20]
+            .iter()
+            .all(|c| c.colocalization.is_none_or(|s| s > 0. && s <= 1.)));
+        assert_eq!(coherence[21], Default::default());
+
+        let coherence = complex_coherence(&mut dag, None, &complexes, &Default::default());
+        assert!(coherence.iter().all(|c| c.coexpression.is_none()));
+    }
+}
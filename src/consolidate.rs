@@ -0,0 +1,169 @@
+/// 合并不同动态子网络中识别出的相同复合物
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    eva::{Complex, MIN_SIZE},
+    graph::Graph,
+};
+
+/// 默认的合并阈值（重叠分数）
+pub const MERGE_OVERLAP_SCORE: f64 = 0.8;
+
+// 合并时成员的取法
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemberPolicy {
+    Union,
+    Intersection,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsolidateOptions {
+    // 重叠分数不小于该值的复合物视为同一个复合物
+    pub threshold: f64,
+    pub members: MemberPolicy,
+}
+
+impl Default for ConsolidateOptions {
+    fn default() -> Self {
+        Self {
+            threshold: MERGE_OVERLAP_SCORE,
+            members: MemberPolicy::Union,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsolidatedComplex {
+    // 内聚力在静态网络上重新计算
+    pub complex: Complex<String>,
+    // 复合物活跃的时间点，从小到大
+    pub time_points: Vec<usize>,
+}
+
+// 按时间点顺序合并复合物，per_time[t] 为第t个动态子网络的识别结果
+pub fn consolidate(
+    graph: &Graph,
+    per_time: &[Vec<Complex<String>>],
+    options: &ConsolidateOptions,
+) -> Vec<ConsolidatedComplex> {
+    let mut merged = Vec::<(Complex<String>, BTreeSet<usize>)>::new();
+    for (t, complexes) in per_time.iter().enumerate() {
+        for complex in complexes.iter() {
+            // 找到重叠分数最大的已有复合物，分数相同时取先出现的
+            let best = merged
+                .iter()
+                .enumerate()
+                .map(|(i, (m, _))| (i, m.overlap_score(complex).1))
+                .filter(|(_, os)| os.ge(&options.threshold))
+                .fold(None, |best: Option<(usize, f64)>, (i, os)| match best {
+                    Some((_, b)) if b >= os => best,
+                    _ => Some((i, os)),
+                });
+
+            match best {
+                Some((i, _)) => {
+                    let (m, times) = &mut merged[i];
+                    m.proteins = merge_members(&m.proteins, &complex.proteins, options.members);
+                    times.insert(t);
+                }
+                None => merged.push((complex.clone(), BTreeSet::from([t]))),
+            }
+        }
+    }
+
+    let protein_id = graph
+        .id_protein
+        .iter()
+        .enumerate()
+        .map(|(id, p)| (p.as_str(), id))
+        .collect::<HashMap<_, _>>();
+    merged
+        .into_iter()
+        .filter(|(m, _)| m.len() >= MIN_SIZE)
+        .map(|(m, times)| {
+            let ids = m
+                .proteins
+                .iter()
+                .filter_map(|p| protein_id.get(p.as_str()).copied())
+                .collect::<Vec<_>>();
+            let cohesion = graph.calculate_cohesion(&ids);
+            ConsolidatedComplex {
+                complex: Complex::new(m.proteins, cohesion),
+                time_points: times.into_iter().collect(),
+            }
+        })
+        .collect()
+}
+
+fn merge_members(a: &[String], b: &[String], policy: MemberPolicy) -> Vec<String> {
+    let a = a.iter().cloned().collect::<BTreeSet<_>>();
+    let b = b.iter().cloned().collect::<BTreeSet<_>>();
+    match policy {
+        MemberPolicy::Union => a.union(&b).cloned().collect(),
+        MemberPolicy::Intersection => a.intersection(&b).cloned().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{eva::Complex, graph::Graph};
+
+    use super::{consolidate, ConsolidateOptions, MemberPolicy};
+
+    fn complex(proteins: &[&str]) -> Complex<String> {
+        Complex::new(proteins.iter().map(|p| p.to_string()).collect(), 0.)
+    }
+
+    fn graph() -> Graph {
+        let mut g = Graph::new_from(vec![
+            (0, 1, 1.),
+            (0, 2, 1.),
+            (1, 2, 1.),
+            (2, 3, 1.),
+            (3, 4, 1.),
+            (4, 5, 1.),
+            (3, 5, 1.),
+        ]);
+        g.id_protein = ["A", "B", "C", "D", "E", "F"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        g
+    }
+
+    #[test]
+    fn test_consolidate_union() {
+        let per_time = vec![
+            vec![complex(&["A", "B", "C"]), complex(&["D", "E", "F"])],
+            vec![],
+            vec![complex(&["A", "B", "C", "D"])],
+        ];
+        let options = ConsolidateOptions {
+            threshold: 0.7,
+            members: MemberPolicy::Union,
+        };
+        let res = consolidate(&graph(), &per_time, &options);
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].complex.proteins, vec!["A", "B", "C", "D"]);
+        assert_eq!(res[0].time_points, vec![0, 2]);
+        assert_eq!(res[1].time_points, vec![0]);
+        assert!(res[0].complex.cohesion > 0.);
+    }
+
+    #[test]
+    fn test_consolidate_intersection() {
+        let per_time = vec![
+            vec![complex(&["A", "B", "C", "D"])],
+            vec![complex(&["A", "B", "C"])],
+            vec![complex(&["A", "B", "C", "E"])],
+        ];
+        let options = ConsolidateOptions {
+            threshold: 0.7,
+            members: MemberPolicy::Intersection,
+        };
+        let res = consolidate(&graph(), &per_time, &options);
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].complex.proteins, vec!["A", "B", "C"]);
+        assert_eq!(res[0].time_points, vec![0, 1, 2]);
+    }
+}
@@ -1,7 +1,10 @@
-use crate::graph::Graph;
+use crate::{
+    gene_expression::{CoExpression, GeneExpress},
+    graph::Graph,
+};
 use std::{
     cmp::Ordering,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fs::read_to_string,
 };
 
@@ -10,9 +13,11 @@ pub const PART_OF_WEIGHT: f64 = 0.6;
 
 #[derive(Debug)]
 pub struct Dag {
-    edges: Vec<HashMap<usize, f64>>,
-    protein_go: HashMap<String, HashSet<usize>>,
+    edges: Vec<BTreeMap<usize, f64>>,
+    protein_go: HashMap<String, BTreeSet<usize>>,
     go_child: HashMap<usize, HashSet<usize>>,
+    // 术语id对应的GO编号
+    go_terms: Vec<String>,
 
     // sim_term只用作计算使用
     sim_term: HashMap<(usize, usize), f64>, // 计算时候更新
@@ -21,12 +26,13 @@ pub struct Dag {
 
 impl Dag {
     pub fn new() -> Self {
-        let (edges, protein_go, go_child) = read_go_file();
+        let (edges, protein_go, go_child, go_terms) = read_go_file();
         let mut dag = Self {
             edges: vec![Default::default(); edges.len()],
             protein_go,
             sim_term: Default::default(), // 计算是更新
             go_child,
+            go_terms,
             sim_term_child: Default::default(), // 计算时更新
         };
 
@@ -120,6 +126,44 @@ impl Dag {
         self.edges[a].insert(b, r);
     }
 
+    // 术语id对应的GO编号
+    pub fn term_name(&self, term: usize) -> &str {
+        self.go_terms.get(term).map_or("", |t| t.as_str())
+    }
+
+    // GO编号对应的术语id
+    pub fn term_id(&self, name: &str) -> Option<usize> {
+        self.go_terms.iter().position(|t| t == name)
+    }
+
+    // 蛋白质直接注释的术语
+    pub fn annotations(&self, protein: &str) -> Option<&BTreeSet<usize>> {
+        self.protein_go.get(protein)
+    }
+
+    // 向上传递后的注释：直接注释的术语及其所有祖先，没有注释的蛋白质不出现在结果中
+    pub fn propagated_annotations<'a>(
+        &self,
+        proteins: impl IntoIterator<Item = &'a str>,
+    ) -> HashMap<String, BTreeSet<usize>> {
+        let mut ancestors = HashMap::<usize, HashSet<usize>>::new();
+        let mut result = HashMap::new();
+        for protein in proteins {
+            let Some(gos) = self.protein_go.get(protein).filter(|g| !g.is_empty()) else {
+                continue;
+            };
+            let mut terms = BTreeSet::new();
+            for go in gos.iter() {
+                let a = ancestors
+                    .entry(*go)
+                    .or_insert_with(|| self.get_ancestors(*go));
+                terms.extend(a.iter().copied());
+            }
+            result.insert(protein.to_string(), terms);
+        }
+        result
+    }
+
     /// 获取某个节点的语义值（包括祖先节点的语义贡献）
     fn calculate_semantic_value(&self, node: usize, memo: &mut HashMap<usize, f64>) {
         if let Some(parents) = self.edges.get(node) {
@@ -379,7 +423,7 @@ impl Dag {
 
         common
             .into_iter()
-            .min_by_key(|&ancestor| dist1[&ancestor] + dist2[&ancestor])
+            .min_by_key(|&ancestor| (dist1[&ancestor] + dist2[&ancestor], ancestor))
     }
 
     /// 获取祖先距离和前驱信息，用于构造路径
@@ -445,7 +489,7 @@ impl Dag {
 
         let lca = common
             .into_iter()
-            .min_by_key(|&ancestor| dist1[&ancestor] + dist2[&ancestor])?;
+            .min_by_key(|&ancestor| (dist1[&ancestor] + dist2[&ancestor], ancestor))?;
 
         let path1 = self.build_path(&pred1, node1, lca);
         let path2 = self.build_path(&pred2, node2, lca);
@@ -465,8 +509,36 @@ pub fn weight_by_dag(graph: &mut Graph) {
 }
 
 pub fn weight_by_dag_topo(graph: &mut Graph, alpha: f64) {
-    let mut dag = Dag::new();
+    weight_by_dag_topo_with(graph, &mut Dag::new(), alpha);
+}
+
+// 复用已经加载的DAG，多个动态子网络之间共享术语相似性缓存
+pub fn weight_by_dag_topo_with(graph: &mut Graph, dag: &mut Dag, alpha: f64) {
+    weight_by_fusion(graph, dag, alpha, |_, _| None);
+}
 
+// 在功能与拓扑相似性的基础上融合共表达相似性:
+// (1 - gamma) * (alpha * 功能 + (1 - alpha) * 拓扑) + gamma * 共表达
+// 任一端没有表达谱的边只使用功能与拓扑相似性
+pub fn weight_by_dag_topo_expr(
+    graph: &mut Graph,
+    dag: &mut Dag,
+    alpha: f64,
+    gamma: f64,
+    express: &GeneExpress,
+    measure: CoExpression,
+) {
+    let id_protein = graph.id_protein.clone();
+    weight_by_fusion(graph, dag, alpha, |a, b| {
+        let coexp = express.coexpression(&id_protein[a], &id_protein[b], measure)?;
+        Some((gamma, coexp))
+    });
+}
+
+fn weight_by_fusion<F>(graph: &mut Graph, dag: &mut Dag, alpha: f64, coexp: F)
+where
+    F: Fn(usize, usize) -> Option<(f64, f64)>,
+{
     // let mut func_sim = HashMap::<(usize, usize), f64>::new();
     // 暂时存储拓扑相似性，以避开借用检查机制
     let mut topo_sim = HashMap::<(usize, usize), f64>::new();
@@ -491,6 +563,10 @@ pub fn weight_by_dag_topo(graph: &mut Graph, alpha: f64) {
 
             let topo_sim = topo_sim.get(&(a, *b)).unwrap();
             *w = alpha * sim + (1. - alpha) * topo_sim;
+            // 共表达相似性
+            if let Some((gamma, coexp)) = coexp(a, *b) {
+                *w = (1. - gamma) * *w + gamma * coexp;
+            }
         });
     });
 
@@ -502,8 +578,9 @@ pub fn weight_by_dag_topo(graph: &mut Graph, alpha: f64) {
 
 fn read_go_file() -> (
     Vec<(usize, usize, f64)>,
-    HashMap<String, HashSet<usize>>,
+    HashMap<String, BTreeSet<usize>>,
     HashMap<usize, HashSet<usize>>,
+    Vec<String>,
 ) {
     let mut go_terms = Vec::<String>::new();
     let mut go_term_id = HashMap::<String, usize>::new();
@@ -567,11 +644,11 @@ fn read_go_file() -> (
     }
 
     // 读取蛋白质到go term的映射
-    let mut protein_go = HashMap::<String, HashSet<usize>>::new();
+    let mut protein_go = HashMap::<String, BTreeSet<usize>>::new();
     let contents = read_to_string("./data/go_slim.txt").expect("Failed to read go slim file!");
     for line in contents.lines() {
         let line = line.split_whitespace().collect::<Vec<_>>();
-        let mut gos = HashSet::<usize>::new();
+        let mut gos = BTreeSet::<usize>::new();
         line[1..].into_iter().for_each(|g| {
             if let Some(id) = go_term_id.get(*g) {
                 gos.insert(*id);
@@ -580,14 +657,14 @@ fn read_go_file() -> (
         protein_go.insert(line[0].to_string(), gos);
     }
 
-    return (edges, protein_go, go_child);
+    return (edges, protein_go, go_child, go_terms);
 }
 
 #[cfg(test)]
 mod tests {
     use super::Dag;
     use crate::graph::Graph;
-    use std::collections::{HashMap, HashSet};
+    use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
     #[test]
     fn test_dag_from_file() {
@@ -600,16 +677,16 @@ mod tests {
     #[test]
     fn test_get_all_ancestor() {
         let a = vec![
-            HashMap::from([(1, 0.6), (2, 0.8), (3, 0.8)]),
-            HashMap::from([(3, 0.8), (4, 0.8)]),
-            HashMap::from([(3, 0.6)]),
-            HashMap::from([(4, 0.6)]),
-            HashMap::new(),
+            BTreeMap::from([(1, 0.6), (2, 0.8), (3, 0.8)]),
+            BTreeMap::from([(3, 0.8), (4, 0.8)]),
+            BTreeMap::from([(3, 0.6)]),
+            BTreeMap::from([(4, 0.6)]),
+            BTreeMap::new(),
         ];
 
         let protien_go = HashMap::from([
-            ("a".to_string(), HashSet::from([1, 2])),
-            ("b".to_string(), HashSet::from([3, 1, 4])),
+            ("a".to_string(), BTreeSet::from([1, 2])),
+            ("b".to_string(), BTreeSet::from([3, 1, 4])),
         ]);
         let dag = Dag {
             edges: a,
@@ -621,6 +698,7 @@ mod tests {
                 (2, HashSet::from([0])),
                 (1, HashSet::from([0])),
             ]),
+            go_terms: Default::default(),
             sim_term_child: Default::default(),
         };
 
@@ -0,0 +1,219 @@
+/// 动态网络：静态网络加上蛋白质在各时间点的活跃状态
+use std::{
+    collections::HashSet,
+    io::{self, BufWriter, Write},
+};
+
+use crate::{gene_expression::DpinBuilder, graph::Graph, writer::create_file};
+
+#[derive(Debug)]
+pub struct DynamicNetwork {
+    // 静态网络，所有时间点共享
+    graph: Graph,
+    // 时间点的列名或时相
+    labels: Vec<String>,
+    // 蛋白质×时间点的活跃矩阵，下标与静态网络的节点id一致
+    activity: Vec<Vec<bool>>,
+}
+
+// 某个时间点的视图，节点id与静态网络一致；
+// 不复制边，按活跃矩阵过滤静态网络的邻接表
+#[derive(Debug, Clone, Copy)]
+pub struct LayerView<'a> {
+    network: &'a DynamicNetwork,
+    t: usize,
+}
+
+impl DynamicNetwork {
+    // activity[node][t] 为节点在第t个时间点是否活跃
+    pub fn new(graph: Graph, labels: Vec<String>, activity: Vec<Vec<bool>>) -> Self {
+        assert_eq!(activity.len(), graph.node_count);
+        assert!(activity.iter().all(|a| a.len() == labels.len()));
+        Self {
+            graph,
+            labels,
+            activity,
+        }
+    }
+
+    // 由每个时间点的活跃蛋白质集合构建
+    pub fn from_active_sets(graph: Graph, labels: Vec<String>, sets: &[HashSet<String>]) -> Self {
+        let activity = graph
+            .id_protein
+            .iter()
+            .map(|p| sets.iter().map(|s| s.contains(p)).collect())
+            .collect();
+        Self::new(graph, labels, activity)
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    pub fn activity(&self) -> &[Vec<bool>] {
+        &self.activity
+    }
+
+    pub fn time_points(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn view(&self, t: usize) -> LayerView<'_> {
+        assert!(t < self.time_points(), "time point out of bound");
+        LayerView { network: self, t }
+    }
+
+    pub fn views(&self) -> impl Iterator<Item = LayerView<'_>> {
+        (0..self.time_points()).map(|t| self.view(t))
+    }
+
+    // 多层边列表: source, target, weight, layer, time
+    // 可直接导入 Cytoscape，也可作为时序网络工具的 (i, j, t) 边列表
+    pub fn write_edge_list(&self, file: &str) -> io::Result<()> {
+        let mut file = BufWriter::new(create_file(file)?);
+        writeln!(file, "source\ttarget\tweight\tlayer\ttime")?;
+        for view in self.views() {
+            for (a, b, w) in view.edges() {
+                writeln!(
+                    file,
+                    "{}\t{}\t{}\t{}\t{}",
+                    self.graph.id_protein[a],
+                    self.graph.id_protein[b],
+                    w,
+                    view.label(),
+                    view.t
+                )?;
+            }
+        }
+        file.flush()
+    }
+
+    // 活跃矩阵: 每行一个蛋白质，每列一个时间点，1为活跃
+    pub fn write_activity(&self, file: &str) -> io::Result<()> {
+        let mut file = BufWriter::new(create_file(file)?);
+        writeln!(file, "protein\t{}", self.labels.join("\t"))?;
+        for (p, active) in self.graph.id_protein.iter().zip(self.activity.iter()) {
+            let active = active
+                .iter()
+                .map(|a| if *a { "1" } else { "0" })
+                .collect::<Vec<_>>();
+            writeln!(file, "{}\t{}", p, active.join("\t"))?;
+        }
+        file.flush()
+    }
+}
+
+impl<'a> LayerView<'a> {
+    pub fn time_point(&self) -> usize {
+        self.t
+    }
+
+    pub fn label(&self) -> &'a str {
+        &self.network.labels[self.t]
+    }
+
+    pub fn is_active(&self, node: usize) -> bool {
+        self.network.activity[node][self.t]
+    }
+
+    // 活跃节点，按id从小到大
+    pub fn nodes(&self) -> impl Iterator<Item = usize> + 'a {
+        let view = *self;
+        (0..view.network.graph.node_count).filter(move |n| view.is_active(*n))
+    }
+
+    // 节点的活跃邻居
+    pub fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, f64)> + 'a {
+        let view = *self;
+        let active = view.is_active(node);
+        view.network.graph.nei_list[node]
+            .iter()
+            .filter(move |(n, _)| active && view.is_active(**n))
+            .map(|(n, w)| (*n, *w))
+    }
+
+    // 两端都活跃的边，a < b
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, f64)> + 'a {
+        let view = *self;
+        self.nodes().flat_map(move |a| {
+            view.neighbors(a)
+                .filter(move |(b, _)| a < *b)
+                .map(move |(b, w)| (a, b, w))
+        })
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes().count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges().count()
+    }
+
+    // 复制为独立的子网络，供需要 Graph 的算法使用；
+    // 子网络的节点i对应 nodes() 的第i个节点
+    pub fn to_graph(&self) -> Graph {
+        let graph = &self.network.graph;
+        let nodes = self.nodes().map(|n| graph.id_protein[n].clone()).collect();
+        graph.subgraph(&nodes)
+    }
+}
+
+impl DpinBuilder {
+    pub fn build_network(&self, graph: Graph) -> DynamicNetwork {
+        let (labels, sets) = self.labeled_active_sets(&graph);
+        DynamicNetwork::from_active_sets(graph, labels, &sets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::graph::Graph;
+
+    use super::DynamicNetwork;
+
+    #[test]
+    fn test_dynamic_network() {
+        let mut g = Graph::new_from(vec![(0, 1, 1.), (1, 2, 0.5), (0, 2, 1.), (2, 3, 1.)]);
+        g.id_protein = ["A", "B", "C", "D"].iter().map(|p| p.to_string()).collect();
+        let sets = [vec!["A", "B", "C"], vec!["B", "C", "D"]]
+            .iter()
+            .map(|s| s.iter().map(|p| p.to_string()).collect::<HashSet<_>>())
+            .collect::<Vec<_>>();
+        let labels = vec!["t0".to_string(), "t1".to_string()];
+        let network = DynamicNetwork::from_active_sets(g, labels, &sets);
+
+        assert_eq!(network.activity()[3], vec![false, true]);
+        let view = network.view(1);
+        assert_eq!(view.nodes().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(
+            view.edges().collect::<Vec<_>>(),
+            vec![(1, 2, 0.5), (2, 3, 1.)]
+        );
+        assert_eq!(view.neighbors(0).count(), 0);
+        assert_eq!(network.view(0).edge_count(), 3);
+
+        // 子网络的节点与视图中的节点一一对应
+        let layer = view.to_graph();
+        assert_eq!(layer.node_count, 3);
+        assert_eq!(layer.id_protein, vec!["B", "C", "D"]);
+
+        let dir = std::env::temp_dir().join(format!("dynamic_{}", std::process::id()));
+        let edges = dir.join("edges.tsv");
+        network.write_edge_list(edges.to_str().unwrap()).unwrap();
+        let edges = std::fs::read_to_string(edges).unwrap();
+        assert_eq!(edges.lines().count(), 6);
+        assert_eq!(edges.lines().last(), Some("C\tD\t1\tt1\t1"));
+        let activity = dir.join("activity.tsv");
+        network.write_activity(activity.to_str().unwrap()).unwrap();
+        let activity = std::fs::read_to_string(activity).unwrap();
+        assert_eq!(activity.lines().nth(4), Some("D\t0\t1"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
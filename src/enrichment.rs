@@ -0,0 +1,213 @@
+/// 复合物的 GO 富集分析：以网络中有注释的蛋白质为背景，注释沿 DAG 向上传递
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::{self, BufWriter, Write},
+};
+
+use crate::{
+    dag::Dag,
+    eva::Complex,
+    graph::Graph,
+    stats::{benjamini_hochberg, hypergeometric_sf},
+    writer::create_file,
+};
+
+/// 默认的 FDR 阈值
+pub const ENRICHMENT_FDR: f64 = 0.05;
+/// 报告中每个复合物输出的术语数
+pub const TOP_TERMS: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct EnrichmentOptions {
+    // 校正后 q 值不大于该值的术语视为显著
+    pub fdr: f64,
+    // 复合物中至少有这么多成员注释到该术语时才检验
+    pub min_count: usize,
+}
+
+impl Default for EnrichmentOptions {
+    fn default() -> Self {
+        Self {
+            fdr: ENRICHMENT_FDR,
+            min_count: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermEnrichment {
+    pub term: String,
+    // 复合物中和背景中注释到该术语的蛋白质数
+    pub count: usize,
+    pub background_count: usize,
+    pub p_value: f64,
+    // 在该复合物检验的所有术语上校正后的 q 值
+    pub q_value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexEnrichment {
+    // 复合物中有注释的成员数
+    pub annotated: usize,
+    // 按 p 值从小到大排列
+    pub terms: Vec<TermEnrichment>,
+}
+
+impl ComplexEnrichment {
+    pub fn is_enriched(&self, fdr: f64) -> bool {
+        self.terms.iter().any(|t| t.q_value <= fdr)
+    }
+
+    pub fn top_terms(&self, n: usize) -> &[TermEnrichment] {
+        &self.terms[..n.min(self.terms.len())]
+    }
+}
+
+// 对每个复合物做超几何检验，以网络中有注释的蛋白质为背景，
+// 不在网络中或没有注释的成员不参与计算
+pub fn enrich_complexes(
+    dag: &Dag,
+    graph: &Graph,
+    complexes: &[Complex<String>],
+    options: &EnrichmentOptions,
+) -> Vec<ComplexEnrichment> {
+    let annotations = dag.propagated_annotations(graph.id_protein.iter().map(|p| p.as_str()));
+    let population = annotations.len() as u64;
+    let mut term_size = BTreeMap::<usize, u64>::new();
+    for terms in annotations.values() {
+        for t in terms {
+            *term_size.entry(*t).or_default() += 1;
+        }
+    }
+
+    complexes
+        .iter()
+        .map(|c| {
+            let members = c
+                .proteins
+                .iter()
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .filter_map(|p| annotations.get(p))
+                .collect::<Vec<_>>();
+            let mut counts = BTreeMap::<usize, u64>::new();
+            for t in members.iter().flat_map(|terms| terms.iter()) {
+                *counts.entry(*t).or_default() += 1;
+            }
+            let members = members.len() as u64;
+
+            let tested = counts
+                .into_iter()
+                .filter(|(_, k)| *k as usize >= options.min_count)
+                .map(|(t, k)| {
+                    let size = term_size[&t];
+                    (t, k, size, hypergeometric_sf(k, population, size, members))
+                })
+                .collect::<Vec<_>>();
+            let q = benjamini_hochberg(&tested.iter().map(|t| t.3).collect::<Vec<_>>());
+            let mut terms = tested
+                .into_iter()
+                .zip(q)
+                .map(|((t, k, size, p), q)| TermEnrichment {
+                    term: dag.term_name(t).to_string(),
+                    count: k as usize,
+                    background_count: size as usize,
+                    p_value: p,
+                    q_value: q,
+                })
+                .collect::<Vec<_>>();
+            terms.sort_by(|a, b| a.p_value.total_cmp(&b.p_value).then(a.term.cmp(&b.term)));
+
+            ComplexEnrichment {
+                annotated: members as usize,
+                terms,
+            }
+        })
+        .collect()
+}
+
+// 至少有一个显著富集术语的复合物比例
+pub fn enriched_fraction(enrichment: &[ComplexEnrichment], fdr: f64) -> f64 {
+    match enrichment.len() {
+        0 => 0.,
+        n => enrichment.iter().filter(|e| e.is_enriched(fdr)).count() as f64 / n as f64,
+    }
+}
+
+// 每个复合物输出前 top 个术语: complex_id, rank, term, count, annotated, background_count, p_value, q_value
+pub fn write_enrichment(
+    file: &str,
+    enrichment: &[ComplexEnrichment],
+    top: usize,
+) -> io::Result<()> {
+    let mut out = BufWriter::new(create_file(file)?);
+    writeln!(
+        out,
+        "complex_id\trank\tterm\tcount\tannotated\tbackground_count\tp_value\tq_value"
+    )?;
+    for (id, e) in enrichment.iter().enumerate() {
+        for (rank, t) in e.top_terms(top).iter().enumerate() {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{:.4e}\t{:.4e}",
+                id,
+                rank + 1,
+                t.term,
+                t.count,
+                e.annotated,
+                t.background_count,
+                t.p_value,
+                t.q_value
+            )?;
+        }
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        dag::Dag,
+        eva::{read_complexes, Complex, COMPLEX_REF},
+        graph::Graph,
+    };
+
+    use super::{enrich_complexes, enriched_fraction, EnrichmentOptions, ENRICHMENT_FDR};
+
+    #[test]
+    fn test_enrich_complexes() {
+        let dag = Dag::new();
+        let graph = Graph::new_from_file("./data/collins/collins.txt", false);
+        let known = graph.id_protein.iter().cloned().collect::<HashSet<_>>();
+        let mut complexes = read_complexes(COMPLEX_REF, Some(&known));
+        complexes.truncate(20);
+        // 随机挑选的蛋白质一般没有富集术语
+        complexes.push(Complex::new(
+            graph
+                .id_protein
+                .iter()
+                .step_by(97)
+                .take(5)
+                .cloned()
+                .collect(),
+            0.,
+        ));
+
+        let enrichment = enrich_complexes(&dag, &graph, &complexes, &Default::default());
+        assert_eq!(enrichment.len(), 21);
+        let top = &enrichment[0].top_terms(1)[0];
+        assert!(top.term.starts_with("GO:"));
+        assert!(top.p_value <= top.q_value);
+        assert!(enriched_fraction(&enrichment, ENRICHMENT_FDR) > 0.5);
+        assert!(!enrichment[20].is_enriched(ENRICHMENT_FDR));
+
+        let strict = EnrichmentOptions {
+            min_count: 100,
+            ..Default::default()
+        };
+        let enrichment = enrich_complexes(&dag, &graph, &complexes, &strict);
+        assert_eq!(enriched_fraction(&enrichment, ENRICHMENT_FDR), 0.);
+    }
+}
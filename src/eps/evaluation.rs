@@ -1,43 +1,183 @@
-use std::{collections::HashSet, hash::Hash};
+use std::{
+    collections::HashSet,
+    hash::Hash,
+    io::{self, BufWriter, Write},
+};
 
-pub fn evalustion<T>(refprotein: &HashSet<T>, preprotein: &[T])
+use crate::writer::{create_file, csv_field};
+
+/// top_n 中使用的排名截断
+pub const TOP_N: [usize; 12] = [
+    100, 200, 300, 400, 500, 600, 700, 800, 900, 1000, 1100, 1200,
+];
+/// top_n 中使用的百分比截断
+pub const TOP_PERCENT: [usize; 6] = [1, 5, 10, 15, 20, 25];
+
+// 排名前 cutoff 个预测为关键蛋白质时的指标，
+// 只有在排名中的参考蛋白质计为正例
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CutoffMetrics {
+    // 实际使用的截断，不超过排名长度
+    pub cutoff: usize,
+    pub tp: usize,
+    pub fp: usize,
+    pub tn: usize,
+    pub fn_: usize,
+    pub sn: f64,
+    pub sp: f64,
+    pub ppv: f64,
+    pub npv: f64,
+    pub f: f64,
+    pub acc: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RankingMetrics {
+    // 与传入的截断一一对应
+    pub cutoffs: Vec<CutoffMetrics>,
+    // 在完整排名上用梯形法计算的 ROC 和 PR 曲线下面积，
+    // 没有正例（ROC 还要求有负例）时无定义，为 None
+    pub auroc: Option<f64>,
+    pub auprc: Option<f64>,
+}
+
+fn ratio(a: usize, b: usize) -> f64 {
+    match b {
+        0 => 0.,
+        _ => a as f64 / b as f64,
+    }
+}
+
+pub fn cutoff_metrics<T>(refprotein: &HashSet<T>, preprotein: &[T], cutoff: usize) -> CutoffMetrics
 where
-    T: Clone + Hash + Eq,
+    T: Hash + Eq,
 {
-    let (mut tn, mut tp, mut ff, mut fp) = (0, 0, 0, 0);
+    let cutoff = cutoff.min(preprotein.len());
+    let tp = preprotein[..cutoff]
+        .iter()
+        .filter(|c| refprotein.contains(*c))
+        .count();
+    let fn_ = preprotein[cutoff..]
+        .iter()
+        .filter(|c| refprotein.contains(*c))
+        .count();
+    let (fp, tn) = (cutoff - tp, preprotein.len() - cutoff - fn_);
 
-    preprotein[0..refprotein.len() + 1]
-        .into_iter()
-        .for_each(|c| {
-            if refprotein.contains(c) {
-                tp += 1;
-            } else {
-                fp += 1;
-            }
-        });
+    let sn = ratio(tp, tp + fn_);
+    let ppv = ratio(tp, tp + fp);
+    let f = match sn + ppv {
+        0. => 0.,
+        sum => 2. * sn * ppv / sum,
+    };
+    CutoffMetrics {
+        cutoff,
+        tp,
+        fp,
+        tn,
+        fn_,
+        sn,
+        sp: ratio(tn, tn + fp),
+        ppv,
+        npv: ratio(tn, fn_ + tn),
+        f,
+        acc: ratio(tp + tn, preprotein.len()),
+    }
+}
 
-    preprotein[refprotein.len() + 1..]
-        .into_iter()
-        .for_each(|c| {
-            if refprotein.contains(c) {
-                ff += 1;
-            } else {
-                tn += 1;
-            }
-        });
+pub fn ranking_metrics<T>(
+    refprotein: &HashSet<T>,
+    preprotein: &[T],
+    cutoffs: &[usize],
+) -> RankingMetrics
+where
+    T: Hash + Eq,
+{
+    RankingMetrics {
+        cutoffs: cutoffs
+            .iter()
+            .map(|k| cutoff_metrics(refprotein, preprotein, *k))
+            .collect(),
+        auroc: auroc(refprotein, preprotein),
+        auprc: auprc(refprotein, preprotein),
+    }
+}
+
+// 排名前k个中参考蛋白质的数量，k超过排名长度时按排名长度计算
+pub fn top_counts<T>(refprotein: &HashSet<T>, preprotein: &[T], cutoffs: &[usize]) -> Vec<usize>
+where
+    T: Hash + Eq,
+{
+    cutoffs
+        .iter()
+        .map(|k| cutoff_metrics(refprotein, preprotein, *k).tp)
+        .collect()
+}
 
-    // println!("my {}, {}, {}, {}", tn, tp, ff, fp);
+// 按排名长度的百分比得到的截断
+pub fn percent_cutoffs(len: usize, percents: &[usize]) -> Vec<usize> {
+    percents.iter().map(|p| len * p / 100).collect()
+}
+
+// 依次把每个位置作为阈值得到的 ROC 曲线面积
+pub fn auroc<T>(refprotein: &HashSet<T>, preprotein: &[T]) -> Option<f64>
+where
+    T: Hash + Eq,
+{
+    let positive = preprotein
+        .iter()
+        .filter(|c| refprotein.contains(*c))
+        .count();
+    let negative = preprotein.len() - positive;
+    if positive == 0 || negative == 0 {
+        return None;
+    }
+    // 每个负例与其之前的正例构成一个正确排序的正负对
+    let mut tp = 0;
+    let mut area = 0;
+    for c in preprotein {
+        if refprotein.contains(c) {
+            tp += 1;
+        } else {
+            area += tp;
+        }
+    }
+    Some(area as f64 / (positive * negative) as f64)
+}
 
-    let sn = tp as f64 / (tp + ff) as f64;
-    let sp = tn as f64 / (tn + fp) as f64;
-    let ppv = tp as f64 / (tp + fp) as f64;
-    let npv = tn as f64 / (ff + tn) as f64;
-    let f = 2. * sn * ppv / (sn + ppv);
-    let acc = (tp + tn) as f64 / (tp + tn + fp + ff) as f64;
+// PR 曲线从 (recall 0, precision 1) 开始，依次经过每个位置的 (recall, precision)
+pub fn auprc<T>(refprotein: &HashSet<T>, preprotein: &[T]) -> Option<f64>
+where
+    T: Hash + Eq,
+{
+    let positive = preprotein
+        .iter()
+        .filter(|c| refprotein.contains(*c))
+        .count();
+    if positive == 0 {
+        return None;
+    }
+    let (mut tp, mut area) = (0, 0.);
+    let (mut recall, mut precision) = (0., 1.);
+    for (k, c) in preprotein.iter().enumerate() {
+        if refprotein.contains(c) {
+            tp += 1;
+        }
+        let (r, p) = (ratio(tp, positive), ratio(tp, k + 1));
+        area += (r - recall) * (p + precision) / 2.;
+        (recall, precision) = (r, p);
+    }
+    Some(area)
+}
+
+pub fn evalustion<T>(refprotein: &HashSet<T>, preprotein: &[T])
+where
+    T: Clone + Hash + Eq,
+{
+    let m = cutoff_metrics(refprotein, preprotein, refprotein.len() + 1);
 
     println!(
         "{:.4} {:.4} {:.4} {:.4} {:.4} {:.4}",
-        sn, sp, ppv, npv, f, acc
+        m.sn, m.sp, m.ppv, m.npv, m.f, m.acc
     );
 }
 
@@ -45,51 +185,87 @@ pub fn top_n<T>(refprotein: &HashSet<T>, preprotein: &[T])
 where
     T: Eq + Hash + Clone,
 {
-    let top_n = [
-        100, 200, 300, 400, 500, 600, 700, 800, 900, 1000, 1100, 1200,
-    ];
-    let pre_cent = [1, 5, 10, 15, 20, 25];
-
-    let mut top = Vec::new();
-    let mut top_add = Vec::new();
-    top_n.into_iter().for_each(|k| {
-        let pre = preprotein[0..k]
-            .into_iter()
-            .map(|c| c.to_owned())
-            .collect::<HashSet<_>>()
-            .intersection(&refprotein)
-            .count();
-        top.push(pre);
-        if top_add.is_empty() {
-            top_add.push(pre);
-        } else {
-            top_add.push(top.last().unwrap() - top[top.len() - 2]);
-        }
-    });
-    top_add.push(top.last().unwrap().clone());
-    println!("top: {:?}", top);
-    println!("top_add: {:?}", top_add);
+    // 相邻截断之间新增的数量，最后为总数
+    let increments = |top: &[usize]| {
+        let mut add = top
+            .iter()
+            .enumerate()
+            .map(|(i, c)| if i == 0 { *c } else { c - top[i - 1] })
+            .collect::<Vec<_>>();
+        add.extend(top.last());
+        add
+    };
 
-    // println!("top%{}")
-    top.clear();
-    top_add.clear();
-    pre_cent.into_iter().for_each(|p| {
-        let pre = preprotein[0..preprotein.len() * p / 100]
-            .into_iter()
-            .map(|c| c.to_owned())
-            .collect::<HashSet<_>>()
-            .intersection(&refprotein)
-            .count();
-        top.push(pre);
-        if top_add.is_empty() {
-            top_add.push(pre);
-        } else {
-            top_add.push(top.last().unwrap() - top[top.len() - 2]);
-        }
-    });
+    let top = top_counts(refprotein, preprotein, &TOP_N);
+    println!("top: {:?}", top);
+    println!("top_add: {:?}", increments(&top));
 
+    let cutoffs = percent_cutoffs(preprotein.len(), &TOP_PERCENT);
+    let top = top_counts(refprotein, preprotein, &cutoffs);
+    let mut add = increments(&top);
+    add.pop();
     println!("top%: {:?}", top);
-    println!("top%_add: {:?}", top_add);
+    println!("top%_add: {:?}", add);
+}
+
+// 一个方法的 jackknife 曲线，counts[k - 1] 为排名前k个中参考蛋白质的数量
+#[derive(Debug, Clone, PartialEq)]
+pub struct JackknifeCurve {
+    pub method: String,
+    pub counts: Vec<usize>,
+}
+
+pub fn jackknife<T>(refprotein: &HashSet<T>, preprotein: &[T], max_k: usize) -> Vec<usize>
+where
+    T: Hash + Eq,
+{
+    preprotein
+        .iter()
+        .take(max_k)
+        .scan(0, |count, c| {
+            if refprotein.contains(c) {
+                *count += 1;
+            }
+            Some(*count)
+        })
+        .collect()
+}
+
+// 多个方法的 jackknife 曲线，每条曲线的长度不超过 max_k 和对应排名的长度
+pub fn jackknife_curves<T>(
+    refprotein: &HashSet<T>,
+    rankings: &[(&str, &[T])],
+    max_k: usize,
+) -> Vec<JackknifeCurve>
+where
+    T: Hash + Eq,
+{
+    rankings
+        .iter()
+        .map(|(method, preprotein)| JackknifeCurve {
+            method: method.to_string(),
+            counts: jackknife(refprotein, preprotein, max_k),
+        })
+        .collect()
+}
+
+// 每行一个k，每列一个方法；排名较短的方法在超出部分留空
+pub fn write_jackknife(file: &str, curves: &[JackknifeCurve]) -> io::Result<()> {
+    let mut out = BufWriter::new(create_file(file)?);
+    let methods = curves
+        .iter()
+        .map(|c| csv_field(&c.method))
+        .collect::<Vec<_>>();
+    writeln!(out, "k,{}", methods.join(","))?;
+    let rows = curves.iter().map(|c| c.counts.len()).max().unwrap_or(0);
+    for k in 0..rows {
+        let counts = curves
+            .iter()
+            .map(|c| c.counts.get(k).map_or(String::new(), |n| n.to_string()))
+            .collect::<Vec<_>>();
+        writeln!(out, "{},{}", k + 1, counts.join(","))?;
+    }
+    out.flush()
 }
 
 // 生成n个点
@@ -172,3 +348,59 @@ where
     }
     pr
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{
+        auprc, auroc, cutoff_metrics, jackknife_curves, ranking_metrics, top_counts,
+        write_jackknife,
+    };
+
+    #[test]
+    fn test_ranking_metrics() {
+        let refprotein = HashSet::from(["a", "b", "c", "x"]);
+        let preprotein = ["a", "d", "b", "e", "c", "f"];
+
+        let m = cutoff_metrics(&refprotein, &preprotein, 3);
+        assert_eq!((m.tp, m.fp, m.tn, m.fn_), (2, 1, 2, 1));
+        assert!((m.sn - 2. / 3.).abs() < 1e-12 && (m.acc - 4. / 6.).abs() < 1e-12);
+
+        // 截断超过排名长度时不会越界
+        let metrics = ranking_metrics(&refprotein, &preprotein, &[1, 100]);
+        assert_eq!(metrics.cutoffs[1].cutoff, 6);
+        assert_eq!(metrics.cutoffs[1].sn, 1.);
+        assert_eq!(top_counts(&refprotein, &preprotein, &[2, 1200]), vec![1, 3]);
+
+        // 9 个正负对中正确排序的有 1 + 2 + 3 个
+        assert!((auroc(&refprotein, &preprotein).unwrap() - 6. / 9.).abs() < 1e-12);
+        let perfect = ["a", "b", "c", "d", "e"];
+        assert_eq!(auroc(&refprotein, &perfect), Some(1.));
+        assert!((auprc(&refprotein, &perfect).unwrap() - 1.).abs() < 1e-12);
+        // 只有正例或没有正例时无定义
+        assert_eq!(auroc(&refprotein, &["a"]), None);
+        assert_eq!(auprc(&refprotein, &["d"]), None);
+    }
+
+    #[test]
+    fn test_jackknife() {
+        let refprotein = HashSet::from(["a", "b", "c"]);
+        let first = ["a", "d", "b", "c"];
+        let second = ["d", "a"];
+        let curves = jackknife_curves(
+            &refprotein,
+            &[("first", &first[..]), ("second, v2", &second[..])],
+            3,
+        );
+        assert_eq!(curves[0].counts, vec![1, 1, 2]);
+        assert_eq!(curves[1].counts, vec![0, 1]);
+
+        let file = std::env::temp_dir().join(format!("jackknife_{}.csv", std::process::id()));
+        let file = file.to_str().unwrap();
+        write_jackknife(file, &curves).unwrap();
+        let table = std::fs::read_to_string(file).unwrap();
+        assert_eq!(table, "k,first,\"second, v2\"\n1,1,0\n2,1,1\n3,2,\n");
+        std::fs::remove_file(file).unwrap();
+    }
+}
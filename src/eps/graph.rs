@@ -4,6 +4,7 @@ use std::{
 };
 
 use ndarray::{Array1, Array2};
+use sprs::{CsMat, TriMat};
 
 use crate::eps::{
     errors::Result,
@@ -11,17 +12,17 @@ use crate::eps::{
     utils::{get_all_go_terms, read_clique, read_label, read_ppi_file, read_protein_go, BiMap},
 };
 
-/// 使用随机游走，因此采用邻接表存储
+/// 使用随机游走，转移矩阵都很稀疏，采用 CSR 存储
 pub struct Graph {
     pub p_count: usize,
     pub label: Vec<bool>, // 需要参与最终的验证，必需要拿到
     pub essential_proteins: HashSet<usize>,
-    pub ppg: Array2<f64>, // 一阶图
-    pub pcg: Array2<f64>, // 一阶高阶关联图
-    pub cpg: Array2<f64>,
+    pub ppg: CsMat<f64>, // 一阶图
+    pub pcg: CsMat<f64>, // 一阶高阶关联图
+    pub cpg: CsMat<f64>,
     pub ccg: Array2<f64>, // 高阶图
-    pub p2g: Array2<f64>, // 蛋白质到go的关联图
-    pub g2p: Array2<f64>, // go term到go term的关联图
+    pub p2g: CsMat<f64>,  // 蛋白质到go的关联图
+    pub g2p: CsMat<f64>,  // go term到蛋白质的关联图
 }
 
 impl Graph {
@@ -30,12 +31,12 @@ impl Graph {
             p_count: 0,
             label: Vec::new(),
             essential_proteins: HashSet::new(),
-            ppg: Array2::<f64>::zeros((pcount, pcount)),
-            pcg: Array2::<f64>::zeros((pcount, ccount)),
-            cpg: Array2::<f64>::zeros((ccount, pcount)),
+            ppg: CsMat::zero((pcount, pcount)),
+            pcg: CsMat::zero((pcount, ccount)),
+            cpg: CsMat::zero((ccount, pcount)),
             ccg: Array2::<f64>::zeros((ccount, ccount)),
-            p2g: Array2::<f64>::zeros((pcount, gcount)),
-            g2p: Array2::<f64>::zeros((gcount, gcount)),
+            p2g: CsMat::zero((pcount, gcount)),
+            g2p: CsMat::zero((gcount, pcount)),
         }
     }
 
@@ -53,7 +54,7 @@ impl Graph {
         let gid = &get_all_go_terms();
         // 蛋白质到go的映射
         let (ptg, gtp) = read_protein_go(&pid, gid)?;
-        let (p2g, g2p) = get_go_protein_matrix(&ptg, &gtp, pid.len(), 26653);
+        let (p2g, g2p) = get_go_protein_matrix(&ptg, &gtp, pid.len(), gid.len());
 
         let label = read_label(&options.label_file, &pid).unwrap();
         let mut essential_proteins = HashSet::new();
@@ -97,32 +98,31 @@ impl Graph {
     }
 }
 
-// go-terms数量为26653
-#[allow(unused)]
+// go term 的数量由读取到的术语数决定
 fn get_go_protein_matrix(
     ptg: &BTreeMap<usize, BTreeSet<usize>>,
     gtp: &BTreeMap<usize, BTreeSet<usize>>,
     p_count: usize,
     g_count: usize,
-) -> (Array2<f64>, Array2<f64>) {
-    let mut ptg_m = Array2::zeros((p_count, 26653));
-    let mut gtp_m = Array2::zeros((26653, p_count));
+) -> (CsMat<f64>, CsMat<f64>) {
+    let mut ptg_m = TriMat::new((p_count, g_count));
+    let mut gtp_m = TriMat::new((g_count, p_count));
 
-    for (p, gos) in ptg.into_iter() {
+    for (p, gos) in ptg.iter() {
         let score = 1. / gos.len() as f64;
-        for go in gos.into_iter() {
-            ptg_m[[*p, *go]] = score;
+        for go in gos.iter() {
+            ptg_m.add_triplet(*p, *go, score);
         }
     }
 
-    for (go, proteins) in gtp.into_iter() {
+    for (go, proteins) in gtp.iter() {
         let score = 1. / proteins.len() as f64;
-        for protein in proteins.into_iter() {
-            gtp_m[[*go, *protein]] = score;
+        for protein in proteins.iter() {
+            gtp_m.add_triplet(*go, *protein, score);
         }
     }
 
-    (ptg_m, gtp_m)
+    (ptg_m.to_csr(), gtp_m.to_csr())
 }
 
 // walkdown matrix
@@ -131,40 +131,46 @@ fn get_matrix_d(
     dim: (usize, usize),
     cid: &BiMap<BTreeSet<usize>>,
     pnei: &BTreeMap<usize, BTreeSet<usize>>,
-) -> Array2<f64> {
-    let mut m = Array2::<f64>::zeros(dim);
+) -> CsMat<f64> {
+    let mut m = TriMat::new(dim);
     let cid_vec = cid.into_vec();
     for (id, c) in cid_vec.into_iter().enumerate() {
         // for (id, c) in cid.into
         let mut score = HashMap::<usize, f64>::new();
         let mut sum = 0.;
         for p in c.iter() {
-            if let Some(nei) = pnei.get(&p) {
-                let com_len = nei.intersection(&c).collect::<Vec<_>>().len();
+            if let Some(nei) = pnei.get(p) {
+                let com_len = nei.intersection(&c).count();
                 sum += com_len as f64;
                 score.insert(*p, com_len as f64);
             } else {
                 score.insert(*p, 0.);
             }
         }
-        // 更新转移概率
+        // 更新转移概率，只存储非零项；
+        // 成员之间没有连边时 0 / 0 为 NaN，会污染整个游走，该复合物不向下游走
+        if sum == 0. {
+            continue;
+        }
         for p in c.iter() {
-            m[[id, *p]] = score[p] / sum;
+            if score[p] > 0. {
+                m.add_triplet(id, *p, score[p] / sum);
+            }
         }
     }
 
-    m
+    m.to_csr()
 }
 
-fn get_matrix((a, b): (usize, usize), nei: &BTreeMap<usize, BTreeSet<usize>>) -> Array2<f64> {
-    let mut m = Array2::<f64>::zeros((a, b));
-    nei.into_iter().for_each(|(a, b)| {
+fn get_matrix((a, b): (usize, usize), nei: &BTreeMap<usize, BTreeSet<usize>>) -> CsMat<f64> {
+    let mut m = TriMat::new((a, b));
+    nei.iter().for_each(|(a, b)| {
         let p = 1.0 / b.len() as f64;
-        b.into_iter().for_each(|j| {
-            m[[*a, *j]] = p;
+        b.iter().for_each(|j| {
+            m.add_triplet(*a, *j, p);
         });
     });
-    m
+    m.to_csr()
 }
 
 // 得到转移矩阵
@@ -197,7 +203,9 @@ mod tests {
 
     use ndarray::{arr1, Array1, Array2};
 
-    use super::{get_matrix, parse_martix, Graph};
+    use crate::eps::utils::BiMap;
+
+    use super::{get_matrix, get_matrix_d, parse_martix, Graph};
 
     #[test]
     fn parse_matrix() {
@@ -224,7 +232,38 @@ mod tests {
             (2, BTreeSet::from([0])),
         ]);
         let m = get_matrix((3, 3), &pnei);
-        dbg!("{}", m);
+        assert_eq!(m.nnz(), 4);
+        assert_eq!(m.get(0, 1), Some(&0.5));
+        assert_eq!(m.get(1, 0), Some(&1.));
+        assert_eq!(m.get(1, 2), None);
+    }
+
+    #[test]
+    fn get_matrix_d_test() {
+        // 复合物 {0, 1, 2} 中 0 与 1、2 相连，复合物 {1, 2, 3} 中只有 2-3 相连
+        let pnei = BTreeMap::from([
+            (0, BTreeSet::from([1usize, 2])),
+            (1, BTreeSet::from([0])),
+            (2, BTreeSet::from([0, 3])),
+            (3, BTreeSet::from([2])),
+        ]);
+        let mut cid = BiMap::new();
+        cid.insert(BTreeSet::from([0usize, 1, 2]));
+        cid.insert(BTreeSet::from([1usize, 2, 3]));
+        // 成员之间没有连边的复合物
+        cid.insert(BTreeSet::from([1usize, 3]));
+        let m = get_matrix_d((3, 4), &cid, &pnei);
+
+        // 与稠密矩阵的结果一致: score / sum，零项不存储
+        assert_eq!(m.nnz(), 5);
+        assert_eq!(m.get(0, 0), Some(&0.5));
+        assert_eq!(m.get(0, 1), Some(&0.25));
+        assert_eq!(m.get(0, 2), Some(&0.25));
+        assert_eq!(m.get(1, 1), None);
+        assert_eq!(m.get(1, 2), Some(&0.5));
+        assert_eq!(m.get(1, 3), Some(&0.5));
+        assert_eq!(m.outer_view(2).unwrap().nnz(), 0);
+        assert!(m.data().iter().all(|v| v.is_finite()));
     }
 
     #[test]
@@ -1,31 +1,60 @@
 use log::debug;
-use ndarray::{Array1, Array2};
+use ndarray::Array1;
+use sprs::CsMat;
 
 use crate::eps::graph::Graph;
 
+/// 一阶图中的重启（teleport）概率
+pub const TELEPORT: f64 = 0.15;
+
 pub trait RandomWalk {
     const MAXITER: usize = usize::MAX;
-    fn get_rw_matrix(&self, alpha: f64, beta: f64) -> Array2<f64>;
+    // 一步游走：state 左乘转移矩阵
+    fn rw_step(&self, state: &Array1<f64>, alpha: f64, beta: f64) -> Array1<f64>;
     fn random_walk(&self, init_state: Array1<f64>, alpha: f64, beta: f64) -> Array1<f64>;
 }
 
+// 行向量左乘稀疏矩阵: x * m
+fn left_mul(x: &Array1<f64>, m: &CsMat<f64>) -> Array1<f64> {
+    let mut y = Array1::<f64>::zeros(m.cols());
+    for (i, row) in m.outer_iterator().enumerate() {
+        if x[i] == 0. {
+            continue;
+        }
+        for (j, v) in row.iter() {
+            y[j] += x[i] * v;
+        }
+    }
+    y
+}
+
 impl RandomWalk for Graph {
-    fn get_rw_matrix(&self, alpha: f64, beta: f64) -> Array2<f64> {
-        debug!("{}", self.ppg);
-        let c = self.ppg.clone() * 0.85
-            + 0.15 / self.ppg.dim().0 as f64 * Array2::<f64>::ones(self.ppg.dim());
-        let w = self.pcg.dot(&self.cpg);
-        let b = self.p2g.dot(&self.g2p); // 蛋白质到go terms之间的游走矩阵
+    // 转移矩阵为 alpha * C + beta * W + (1 - alpha - beta) * B，其中
+    // C = 0.85 * ppg + 0.15 / n * 全1矩阵，W = pcg * cpg，B = p2g * g2p，
+    // 不显式构造这些稠密矩阵：全1矩阵的作用是秩一修正 sum(x) / n，
+    // W 和 B 依次与两个稀疏矩阵相乘
+    fn rw_step(&self, state: &Array1<f64>, alpha: f64, beta: f64) -> Array1<f64> {
+        let n = self.ppg.rows() as f64;
+        let c = left_mul(state, &self.ppg) * (1. - TELEPORT) + TELEPORT / n * state.sum();
+        let w = left_mul(&left_mul(state, &self.pcg), &self.cpg);
+        let b = left_mul(&left_mul(state, &self.p2g), &self.g2p); // 蛋白质到go terms之间的游走
 
         c * alpha + w * beta + b * (1. - alpha - beta)
     }
 
     fn random_walk(&self, init_state: Array1<f64>, alpha: f64, beta: f64) -> Array1<f64> {
         println!("a: {}, b: {}", alpha, beta);
-        let matrix = self.get_rw_matrix(alpha, beta);
+        debug!(
+            "nnz: ppg {}, pcg {}, cpg {}, p2g {}, g2p {}",
+            self.ppg.nnz(),
+            self.pcg.nnz(),
+            self.cpg.nnz(),
+            self.p2g.nnz(),
+            self.g2p.nnz()
+        );
         let mut cu_state = init_state;
         for _ in 0..Self::MAXITER {
-            let temp_state = cu_state.dot(&matrix);
+            let temp_state = self.rw_step(&cu_state, alpha, beta);
             if (&temp_state - &cu_state).mapv(|x| x.abs()).sum() < 1e-10 {
                 break;
             }
@@ -38,3 +67,53 @@ impl RandomWalk for Graph {
         cu_state
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use ndarray::{arr1, arr2, Array2};
+    use sprs::TriMat;
+
+    use crate::eps::graph::Graph;
+
+    use super::{RandomWalk, TELEPORT};
+
+    #[test]
+    fn rw_step_test() {
+        let ppg = arr2(&[[0., 0.5, 0.5], [1., 0., 0.], [1., 0., 0.]]);
+        let pcg = arr2(&[[1.], [1.], [0.]]);
+        let cpg = arr2(&[[0.5, 0.5, 0.]]);
+        let p2g = arr2(&[[0.5, 0.5], [0., 1.], [1., 0.]]);
+        let g2p = arr2(&[[0.5, 0., 0.5], [0.5, 0.5, 0.]]);
+        // 逐个元素构建，不在 sprs 与 ndarray 之间传递数组类型
+        let sparse = |m: &Array2<f64>| {
+            let mut t = TriMat::new(m.dim());
+            m.indexed_iter()
+                .filter(|(_, v)| **v != 0.)
+                .for_each(|((i, j), v)| t.add_triplet(i, j, *v));
+            t.to_csr()
+        };
+        let g = Graph {
+            p_count: 3,
+            label: vec![false; 3],
+            essential_proteins: HashSet::new(),
+            ppg: sparse(&ppg),
+            pcg: sparse(&pcg),
+            cpg: sparse(&cpg),
+            ccg: Array2::default((1, 1)),
+            p2g: sparse(&p2g),
+            g2p: sparse(&g2p),
+        };
+
+        // 与稠密转移矩阵的结果一致
+        let (alpha, beta) = (0.5, 0.3);
+        let dense = (ppg * (1. - TELEPORT) + TELEPORT / 3. * Array2::<f64>::ones((3, 3))) * alpha
+            + pcg.dot(&cpg) * beta
+            + p2g.dot(&g2p) * (1. - alpha - beta);
+        let state = arr1(&[0.5, 0.3, 0.2]);
+        let step = g.rw_step(&state, alpha, beta);
+        let expected = state.dot(&dense);
+        assert!((step - expected).mapv(f64::abs).sum() < 1e-12);
+    }
+}
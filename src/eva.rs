@@ -1,11 +1,21 @@
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, BTreeSet, HashSet},
     fmt::{self, Display},
     fs::read_to_string,
     hash::Hash,
+    io::{self, BufWriter, Write},
+    str::FromStr,
 };
 
-use crate::graph::Graph;
+use rayon::prelude::*;
+
+use crate::{
+    complex::union::UnionFind,
+    graph::Graph,
+    matching::max_weight_matching,
+    stats::percentile,
+    writer::{create_file, csv_field},
+};
 
 pub const THRESHOLD_OS: f64 = 0.2;
 pub const COMPLEX_REF: &str = "./data/complex.txt";
@@ -49,6 +59,19 @@ where
         (comsize, os)
     }
 
+    // 与大小相关的重叠判断：较小复合物中被共享的比例超过阈值时视为重叠，
+    // 阈值随较小复合物的规模减小而升高，规模为 MIN_SIZE 时只有完全包含才算重叠
+    pub fn is_overlapped_size_aware(&self, other: &Self) -> bool {
+        let set_self: HashSet<_> = self.proteins.iter().collect();
+        let set_other: HashSet<_> = other.proteins.iter().collect();
+        let comsize = set_self.intersection(&set_other).count() as f64;
+
+        let min_size = usize::min(set_other.len(), set_self.len()).max(1) as f64;
+        let threshold = OVERLAP_SCORE + (1. - OVERLAP_SCORE) * (MIN_SIZE as f64 / min_size).min(1.);
+
+        (comsize / min_size).ge(&threshold)
+    }
+
     // 重叠程度
     pub fn is_overlapped(&self, other: &Self) -> bool {
         let set_self: HashSet<_> = self.proteins.iter().collect();
@@ -100,101 +123,699 @@ impl Graph {
     }
 }
 
+// 复合物在内聚力筛选中的去向
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Selection {
+    Kept,
+    // 与已保留的复合物重叠，记录该复合物的下标
+    Overlapped(usize),
+    // 合并到其他复合物中，记录合并后复合物中内聚力最高的成员下标
+    Merged(usize),
+    // 未通过内聚力截断
+    Truncated,
+}
+
+// 重叠复合物的处理策略
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverlapStrategy {
+    // 丢弃与已保留复合物重叠的复合物
+    Discard,
+    // 合并到与之重叠的已保留复合物中
+    Merge,
+    // 重叠比例低于与复合物大小相关的阈值时两者都保留
+    SizeAware,
+    // ClusterONE: 重叠分数不小于阈值的复合物对所在的连通分量合并为一个复合物
+    ClusterOne(f64),
+}
+
+// 处理完重叠之后按内聚力截断的方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CohesionCut {
+    // 保留内聚力排名前 fraction 的复合物
+    Fraction(f64),
+    // 保留内聚力不低于该百分位数(0~100)的复合物
+    Percentile(f64),
+    // 全部保留
+    Keep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelectOptions {
+    pub overlap: OverlapStrategy,
+    pub cut: CohesionCut,
+}
+
+impl Default for SelectOptions {
+    fn default() -> Self {
+        Self {
+            overlap: OverlapStrategy::Discard,
+            cut: CohesionCut::Fraction(0.4),
+        }
+    }
+}
+
 // 根据内聚力更新结果
-pub fn update_by_cohesion<T>(mut complexes: Vec<Complex<T>>) -> Vec<Complex<T>>
+pub fn update_by_cohesion<T>(complexes: Vec<Complex<T>>) -> Vec<Complex<T>>
+where
+    T: Eq + Hash + Clone + Ord,
+{
+    // 丢弃策略不会合并复合物，因此不需要重新计算内聚力
+    select_by_cohesion(&complexes, &SelectOptions::default(), |_| {
+        unreachable!("discard strategy never merges complexes")
+    })
+    .0
+}
+
+// 处理重叠并按内聚力截断，返回保留的复合物(按内聚力从大到小)以及每个输入复合物的去向
+// cohesion 用于重新计算合并后复合物的内聚力
+pub fn select_by_cohesion<T, F>(
+    complexes: &[Complex<T>],
+    options: &SelectOptions,
+    cohesion: F,
+) -> (Vec<Complex<T>>, Vec<Selection>)
 where
     T: Eq + Hash + Clone + Ord,
+    F: Fn(&[T]) -> f64,
 {
-    // 从大到小排序
-    complexes.sort_by(|a, b| a.cohesion.partial_cmp(&b.cohesion).unwrap());
-    complexes.reverse();
-
-    let mut res = Vec::<Complex<T>>::new();
-    complexes.into_iter().for_each(|c| {
-        let mut flag = true;
-        for m in res.iter() {
-            if c.is_overlapped(&m) {
-                flag = false;
-                break;
+    // 从大到小排序，内聚力相同时保持原有顺序
+    let mut order = (0..complexes.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| complexes[*b].cohesion.total_cmp(&complexes[*a].cohesion));
+
+    let mut selection = vec![Selection::Truncated; complexes.len()];
+    // (内聚力最高的成员下标, 复合物)
+    let mut groups = Vec::<(usize, Complex<T>)>::new();
+    match options.overlap {
+        OverlapStrategy::Discard | OverlapStrategy::SizeAware => {
+            for i in order {
+                let c = &complexes[i];
+                let conflict = groups.iter().find(|(_, g)| match options.overlap {
+                    OverlapStrategy::SizeAware => c.is_overlapped_size_aware(g),
+                    _ => c.is_overlapped(g),
+                });
+                match conflict {
+                    Some((k, _)) => selection[i] = Selection::Overlapped(*k),
+                    None => groups.push((i, c.clone())),
+                }
+            }
+        }
+        OverlapStrategy::Merge => {
+            for i in order {
+                let c = &complexes[i];
+                match groups.iter_mut().find(|(_, g)| c.is_overlapped(g)) {
+                    Some((k, g)) => {
+                        g.proteins = union_members(&[g, c]);
+                        g.cohesion = cohesion(&g.proteins);
+                        selection[i] = Selection::Merged(*k);
+                    }
+                    None => groups.push((i, c.clone())),
+                }
             }
         }
+        OverlapStrategy::ClusterOne(threshold) => {
+            let mut pairs = Vec::new();
+            for i in 0..complexes.len() {
+                for j in i + 1..complexes.len() {
+                    if complexes[i].overlap_score(&complexes[j]).1.ge(&threshold) {
+                        pairs.push((i, j));
+                    }
+                }
+            }
+            let mut rank = vec![0; complexes.len()];
+            order.iter().enumerate().for_each(|(r, i)| rank[*i] = r);
+            let mut components = UnionFind::new_from(complexes.len(), pairs).get_components();
+            components
+                .iter_mut()
+                .for_each(|c| c.sort_by_key(|i| rank[*i]));
+            components.sort_by_key(|c| rank[c[0]]);
 
-        if flag {
-            res.push(c);
+            for component in components {
+                let leader = component[0];
+                if component.len() == 1 {
+                    groups.push((leader, complexes[leader].clone()));
+                    continue;
+                }
+                let members = component.iter().map(|i| &complexes[*i]).collect::<Vec<_>>();
+                let proteins = union_members(&members);
+                let merged = Complex::new(proteins.clone(), cohesion(&proteins));
+                component[1..]
+                    .iter()
+                    .for_each(|i| selection[*i] = Selection::Merged(leader));
+                groups.push((leader, merged));
+            }
         }
-    });
+    }
+
+    // 合并后内聚力发生变化，重新排序
+    groups.sort_by(|a, b| b.1.cohesion.total_cmp(&a.1.cohesion));
+    let keep = match options.cut {
+        CohesionCut::Fraction(fraction) => (groups.len() as f64 * fraction) as usize,
+        CohesionCut::Percentile(p) => {
+            let threshold = percentile(
+                &groups.iter().map(|(_, g)| g.cohesion).collect::<Vec<_>>(),
+                p,
+            );
+            groups
+                .iter()
+                .take_while(|(_, g)| g.cohesion.ge(&threshold))
+                .count()
+        }
+        CohesionCut::Keep => groups.len(),
+    };
+    groups.truncate(keep);
+    groups
+        .iter()
+        .for_each(|(k, _)| selection[*k] = Selection::Kept);
+
+    (groups.into_iter().map(|(_, g)| g).collect(), selection)
+}
 
-    res[0..(res.len() as f64 / 2.5) as usize]
+fn union_members<T>(complexes: &[&Complex<T>]) -> Vec<T>
+where
+    T: Clone + Ord,
+{
+    complexes
+        .iter()
+        .flat_map(|c| c.proteins.iter().cloned())
+        .collect::<BTreeSet<_>>()
         .into_iter()
-        .map(|c| c.clone())
-        .collect::<Vec<Complex<T>>>()
+        .collect()
 }
 
-// !!!! Does not stable
-pub fn confucion_matrix<T>(refc: Vec<Complex<T>>, prec: Vec<Complex<T>>)
+/// ClusterONE 中 fraction matched 使用的重叠分数阈值
+pub const THRESHOLD_FRAC: f64 = 0.25;
+
+// 与 evaluation/match.py 一致的评价指标
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Scores {
+    // 参与评价的参考复合物和预测复合物数量
+    pub reference: usize,
+    pub predicted: usize,
+    // 与参考复合物重叠分数大于阈值的预测复合物比例
+    pub precision: f64,
+    // 与预测复合物重叠分数大于阈值的参考复合物比例
+    pub recall: f64,
+    pub f_score: f64,
+    pub sn: f64,
+    pub ppv: f64,
+    // sqrt(Sn * PPV)
+    pub acc: f64,
+    // 与预测复合物重叠分数不小于 THRESHOLD_FRAC 的参考复合物比例
+    pub frac: f64,
+    // Brohée & van Helden 的分离度
+    pub sep: f64,
+    pub mmr: f64,
+}
+
+impl Display for Scores {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Precision: {:.4}\tRecall: {:.4}\tF-score: {:.4}\tSn: {:.4}\tPPV: {:.4}\tAcc: {:.4}\tFrac: {:.4}\tSep: {:.4}\tMMR: {:.4}",
+            self.precision,
+            self.recall,
+            self.f_score,
+            self.sn,
+            self.ppv,
+            self.acc,
+            self.frac,
+            self.sep,
+            self.mmr
+        )
+    }
+}
+
+// 参考复合物×预测复合物的公共蛋白质数量和重叠分数
+struct OverlapMatrix {
+    common: Vec<Vec<f64>>,
+    os: Vec<Vec<f64>>,
+}
+
+impl OverlapMatrix {
+    fn new<T>(refc: &[Complex<T>], prec: &[Complex<T>]) -> Self
+    where
+        T: Eq + Hash + Clone + Ord,
+    {
+        let mut common = vec![vec![0.; prec.len()]; refc.len()];
+        let mut os = vec![vec![0.; prec.len()]; refc.len()];
+        for (i, r) in refc.iter().enumerate() {
+            for (j, p) in prec.iter().enumerate() {
+                (common[i][j], os[i][j]) = r.overlap_score(p);
+            }
+        }
+        Self { common, os }
+    }
+}
+
+// 计算参考复合物与预测复合物之间的评价指标，重叠分数大于 threshold 时视为匹配
+pub fn evaluate<T>(refc: &[Complex<T>], prec: &[Complex<T>], threshold: f64) -> Scores
 where
     T: Eq + Hash + Clone + Ord,
 {
-    let mut matrix_os = vec![vec![f64::default(); prec.len()]; refc.len()];
-    let mut matrix_num = vec![vec![usize::default(); prec.len()]; refc.len()];
-    for i in 0..refc.len() {
-        for j in 0..prec.len() {
-            let (commisze, os) = refc[i].overlap_score(&prec[i]);
-            matrix_os[i][j] = os;
-            matrix_num[i][j] = commisze as usize;
-        }
+    let (n, m) = (refc.len(), prec.len());
+    let matrix = OverlapMatrix::new(refc, prec);
+    let ratio = |count: usize, total: usize| match total {
+        0 => 0.,
+        _ => count as f64 / total as f64,
+    };
+
+    let recall = ratio(
+        (0..n)
+            .filter(|i| (0..m).any(|j| matrix.os[*i][j] > threshold))
+            .count(),
+        n,
+    );
+    let precision = ratio(
+        (0..m)
+            .filter(|j| (0..n).any(|i| matrix.os[i][*j] > threshold))
+            .count(),
+        m,
+    );
+    let f_score = match recall * precision {
+        0. => 0.,
+        _ => 2. * recall * precision / (recall + precision),
+    };
+    let frac = ratio(
+        (0..n)
+            .filter(|i| (0..m).any(|j| matrix.os[*i][j] >= THRESHOLD_FRAC))
+            .count(),
+        n,
+    );
+
+    // Sn: 每个参考复合物被单个预测复合物覆盖的最大蛋白质数
+    let sn_num = matrix
+        .common
+        .iter()
+        .map(|row| row.iter().copied().fold(0., f64::max))
+        .sum::<f64>();
+    let sn_den = refc.iter().map(|c| c.len()).sum::<usize>() as f64;
+    let sn = if m == 0 || sn_den == 0. {
+        0.
+    } else {
+        sn_num / sn_den
+    };
+
+    // PPV: 预测复合物与单个参考复合物的最大公共蛋白质数占与所有参考复合物公共蛋白质数之和的比例
+    let (mut ppv_num, mut ppv_den) = (0., 0.);
+    for j in 0..m {
+        ppv_num += (0..n).map(|i| matrix.common[i][j]).fold(0., f64::max);
+        ppv_den += (0..n).map(|i| matrix.common[i][j]).sum::<f64>();
+    }
+    let ppv = if ppv_den == 0. { 0. } else { ppv_num / ppv_den };
+
+    Scores {
+        reference: n,
+        predicted: m,
+        precision,
+        recall,
+        f_score,
+        sn,
+        ppv,
+        acc: (sn * ppv).sqrt(),
+        frac,
+        sep: separation(&matrix.common),
+        mmr: maximum_matching_ratio(&matrix.os, threshold).mmr,
     }
+}
+
+// sep_ij = T_ij^2 / (sum_j T_ij * sum_i T_ij)，分离度为 sqrt(Sep_co * Sep_cl)
+fn separation(common: &[Vec<f64>]) -> f64 {
+    let (n, m) = (common.len(), common.first().map_or(0, |r| r.len()));
+    if n == 0 || m == 0 {
+        return 0.;
+    }
+    let row_sum = common
+        .iter()
+        .map(|r| r.iter().sum::<f64>())
+        .collect::<Vec<_>>();
+    let col_sum = (0..m)
+        .map(|j| common.iter().map(|r| r[j]).sum::<f64>())
+        .collect::<Vec<_>>();
 
-    // precision
-    let mut count = 0;
-    for i in 0..prec.len() {
-        for j in 0..refc.len() {
-            if matrix_os[j][i].ge(&THRESHOLD_OS) {
-                count += 1;
-                break;
+    let mut total = 0.;
+    for i in 0..n {
+        for j in 0..m {
+            if common[i][j] > 0. {
+                total += common[i][j].powi(2) / (row_sum[i] * col_sum[j]);
             }
         }
     }
-    let precison = count as f64 / prec.len() as f64;
-    println!("precision: {:.4?}", precison);
+    (total / n as f64 * total / m as f64).sqrt()
+}
+
+// 最大匹配：参考复合物与预测复合物之间的一一匹配
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MaxMatching {
+    // 匹配的重叠分数之和除以参考复合物数量
+    pub mmr: f64,
+    // (参考复合物下标, 预测复合物下标, 重叠分数)
+    pub pairs: Vec<(usize, usize, f64)>,
+}
+
+// 与 match.py 一致：只考虑重叠分数大于阈值的复合物对，求重叠分数之和最大的匹配
+pub fn maximum_matching<T>(refc: &[Complex<T>], prec: &[Complex<T>], threshold: f64) -> MaxMatching
+where
+    T: Eq + Hash + Clone + Ord,
+{
+    maximum_matching_ratio(&OverlapMatrix::new(refc, prec).os, threshold)
+}
 
-    let mut count = 0;
-    for i in 0..refc.len() {
-        for j in 0..prec.len() {
-            if matrix_os[j][i].ge(&THRESHOLD_OS) {
-                count += 1;
-                break;
+fn maximum_matching_ratio(os: &[Vec<f64>], threshold: f64) -> MaxMatching {
+    let weights = os
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|s| if *s > threshold { *s } else { 0. })
+                .collect()
+        })
+        .collect::<Vec<_>>();
+    let pairs = max_weight_matching(&weights);
+    let mmr = match os.len() {
+        0 => 0.,
+        n => pairs.iter().fold(0., |acc, (_, _, s)| acc + s) / n as f64,
+    };
+    MaxMatching { mmr, pairs }
+}
+
+// 参考复合物与其最佳匹配的预测复合物
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceMatch<T> {
+    pub reference: usize,
+    // 重叠分数最大的预测复合物，没有公共蛋白质时为None
+    pub best: Option<usize>,
+    pub os: f64,
+    // 与最佳预测复合物共有的蛋白质和未被覆盖的蛋白质
+    pub shared: Vec<T>,
+    pub missing: Vec<T>,
+    // 重叠分数大于阈值
+    pub matched: bool,
+}
+
+// 预测复合物与其最佳匹配的参考复合物，未匹配的为新发现的复合物
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredictionMatch {
+    pub predicted: usize,
+    pub best: Option<usize>,
+    pub os: f64,
+    pub matched: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchReport<T> {
+    pub references: Vec<ReferenceMatch<T>>,
+    pub predictions: Vec<PredictionMatch>,
+}
+
+// 重叠分数最大的下标，相同时取下标小的，全为0时为None
+fn best_match(scores: impl Iterator<Item = f64>) -> Option<(usize, f64)> {
+    scores
+        .enumerate()
+        .filter(|(_, s)| *s > 0.)
+        .fold(None, |best, (j, s)| match best {
+            Some((_, b)) if b >= s => best,
+            _ => Some((j, s)),
+        })
+}
+
+// 每个参考复合物和每个预测复合物的最佳匹配，重叠分数大于 threshold 时视为匹配
+pub fn match_report<T>(refc: &[Complex<T>], prec: &[Complex<T>], threshold: f64) -> MatchReport<T>
+where
+    T: Eq + Hash + Clone + Ord,
+{
+    let matrix = OverlapMatrix::new(refc, prec);
+
+    let references = refc
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let best = best_match(matrix.os[i].iter().copied());
+            let members = best.map_or(HashSet::new(), |(j, _)| {
+                prec[j].proteins.iter().collect::<HashSet<_>>()
+            });
+            let (mut shared, mut missing): (Vec<T>, Vec<T>) = r
+                .proteins
+                .iter()
+                .cloned()
+                .partition(|p| members.contains(p));
+            shared.sort();
+            missing.sort();
+            let os = best.map_or(0., |(_, s)| s);
+            ReferenceMatch {
+                reference: i,
+                best: best.map(|(j, _)| j),
+                os,
+                shared,
+                missing,
+                matched: os > threshold,
+            }
+        })
+        .collect();
+
+    let predictions = (0..prec.len())
+        .map(|j| {
+            let best = best_match(matrix.os.iter().map(|row| row[j]));
+            let os = best.map_or(0., |(_, s)| s);
+            PredictionMatch {
+                predicted: j,
+                best: best.map(|(i, _)| i),
+                os,
+                matched: os > threshold,
             }
+        })
+        .collect();
+
+    MatchReport {
+        references,
+        predictions,
+    }
+}
+
+impl<T> MatchReport<T>
+where
+    T: Display,
+{
+    // 每行一个参考复合物: reference, best_prediction, os, matched, shared, missing
+    // 蛋白质以逗号分隔，没有匹配的预测复合物时为 -；
+    // names 为参考复合物的名称（如 catalog::split_names 的结果），为空时使用下标
+    pub fn write_references(&self, file: &str, names: &[String]) -> io::Result<()> {
+        let join = |ps: &[T]| {
+            ps.iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let mut out = BufWriter::new(create_file(file)?);
+        writeln!(
+            out,
+            "reference\tbest_prediction\tos\tmatched\tshared\tmissing"
+        )?;
+        for r in self.references.iter() {
+            writeln!(
+                out,
+                "{}\t{}\t{:.4}\t{}\t{}\t{}",
+                reference_name(names, r.reference),
+                r.best.map_or("-".to_string(), |j| j.to_string()),
+                r.os,
+                if r.matched { "yes" } else { "no" },
+                join(&r.shared),
+                join(&r.missing)
+            )?;
+        }
+        out.flush()
+    }
+
+    // 每行一个预测复合物: prediction, best_reference, os，没有匹配的参考复合物时记为 novel
+    pub fn write_predictions(&self, file: &str, names: &[String]) -> io::Result<()> {
+        let mut out = BufWriter::new(create_file(file)?);
+        writeln!(out, "prediction\tbest_reference\tos")?;
+        for p in self.predictions.iter() {
+            let best = match p.best {
+                Some(i) if p.matched => reference_name(names, i),
+                _ => "novel".to_string(),
+            };
+            writeln!(out, "{}\t{}\t{:.4}", p.predicted, best, p.os)?;
         }
+        out.flush()
+    }
+}
+
+fn reference_name(names: &[String], i: usize) -> String {
+    names.get(i).cloned().unwrap_or(i.to_string())
+}
+
+pub fn confucion_matrix<T>(refc: Vec<Complex<T>>, prec: Vec<Complex<T>>)
+where
+    T: Eq + Hash + Clone + Ord,
+{
+    let scores = evaluate(&refc, &prec, THRESHOLD_OS);
+    println!("precision: {:.4?}", scores.precision);
+    println!("Recall: {:.4?}", scores.recall);
+    println!("fscore: {:.4?}", scores.f_score);
+    println!("Sn: {:.4?}", scores.sn);
+    println!("PPV: {:.4}", scores.ppv);
+    println!("Acc: {:.4?}", scores.acc);
+}
+
+// 与 match.py 的 read_network 一致：网络中所有非数值的字段，统一为大写
+pub fn read_known_proteins(ppi_file: &str) -> HashSet<String> {
+    let contents = read_to_string(ppi_file).expect("Failed to read ppi file!");
+    contents
+        .lines()
+        .flat_map(|line| line.split_whitespace())
+        .filter(|p| p.parse::<f64>().is_err())
+        .map(|p| p.trim().to_uppercase())
+        .collect()
+}
+
+// 与 match.py 的 read_complexes 一致的读取方式：
+// 限制在网络中的蛋白质，网络中的成员少于 max(3, 一半) 或多于25个时忽略，
+// 除去以C开头的字段（内聚力等数值）后成员数需在[3, 25]之间，
+// 出现多次的复合物与 match.py 一样全部删除（不保留任何一个）
+pub fn read_complexes(file: &str, known: Option<&HashSet<String>>) -> Vec<Complex<String>> {
+    let contents = read_to_string(file).expect("Failed to read complex file!");
+    let mut complexes = Vec::<BTreeSet<String>>::new();
+    for line in contents.lines() {
+        let ps = line
+            .split_whitespace()
+            .map(|p| p.trim().to_uppercase())
+            .collect::<BTreeSet<_>>();
+        let ps = match known {
+            None => ps,
+            Some(known) => {
+                let isect = ps
+                    .iter()
+                    .filter(|p| known.contains(*p))
+                    .cloned()
+                    .collect::<BTreeSet<_>>();
+                let rest = ps.iter().filter(|p| !p.starts_with('C')).count();
+                if (isect.len() as f64) < f64::max(3., rest as f64 * 0.5)
+                    || isect.len() > 25
+                    || !(3..=25).contains(&rest)
+                {
+                    continue;
+                }
+                isect
+            }
+        };
+        complexes.push(ps);
     }
-    let reccall = count as f64 / refc.len() as f64;
-    println!("Recall: {:.4?}", reccall);
-    let f1 = 2.0 * precison * reccall / (reccall + precison);
-    println!("fscore: {:.4?}", f1);
 
-    // 分子
-    // let sum1 = matrix_num.iter().map(|v| *v.iter().max().unwrap()).sum::<usize>();
-    let sum1 = matrix_num
+    let mut counts = BTreeMap::<&BTreeSet<String>, usize>::new();
+    complexes
         .iter()
-        .map(|v| *v.iter().max().unwrap())
-        .sum::<usize>();
-    let sum2 = refc.iter().map(|c| c.proteins.len()).sum::<usize>();
-    let sn = sum1 as f64 / sum2 as f64;
-    println!("Sn: {:.4?}", sn);
-
-    let mut sum1 = 0;
-    for i in 0..prec.len() {
-        let mut max = 0;
-        for j in 0..refc.len() {
-            max = usize::max(max, matrix_num[j][i]);
+        .for_each(|c| *counts.entry(c).or_default() += 1);
+    let unique = complexes.iter().map(|c| counts[c] == 1).collect::<Vec<_>>();
+    complexes
+        .into_iter()
+        .zip(unique)
+        .filter(|(_, unique)| *unique)
+        .map(|(c, _)| c)
+        .map(|c| Complex::new(c.into_iter().collect(), 0.))
+        .collect()
+}
+
+// 评价结果表的排序指标
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SortBy {
+    #[default]
+    FScore,
+    Acc,
+}
+
+impl FromStr for SortBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "f" | "fscore" | "f-score" | "f_score" => Ok(Self::FScore),
+            "acc" => Ok(Self::Acc),
+            _ => Err(format!("unknown sort key: {}", s)),
         }
-        sum1 += max;
     }
-    let sum2 = prec.iter().map(|c| c.proteins.len()).sum::<usize>();
-    let ppv = sum1 as f64 / sum2 as f64;
-    println!("PPV: {:.4}", ppv);
-    let acc = ppv.sqrt();
-    println!("Acc: {:.4?}", acc);
+}
+
+// 单个预测结果文件的评价指标
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileScores {
+    pub file: String,
+    pub scores: Scores,
+}
+
+// 并行评价多个预测结果文件，预测复合物与参考复合物一样限制在网络中，
+// 结果按 sort_by 从大到小排列，相同时按文件名排列
+pub fn evaluate_files(
+    refc: &[Complex<String>],
+    files: &[String],
+    known: &HashSet<String>,
+    sort_by: SortBy,
+) -> Vec<FileScores> {
+    let mut rows = files
+        .par_iter()
+        .map(|file| FileScores {
+            file: file.clone(),
+            scores: evaluate(refc, &read_complexes(file, Some(known)), THRESHOLD_OS),
+        })
+        .collect::<Vec<_>>();
+    let key = |s: &Scores| match sort_by {
+        SortBy::FScore => s.f_score,
+        SortBy::Acc => s.acc,
+    };
+    rows.sort_by(|a, b| {
+        key(&b.scores)
+            .total_cmp(&key(&a.scores))
+            .then(a.file.cmp(&b.file))
+    });
+    rows
+}
+
+// 每行一个文件，每列一个指标；后缀为 csv 时以逗号分隔，否则以制表符分隔
+pub fn write_scores(file: &str, rows: &[FileScores]) -> io::Result<()> {
+    let csv = file.ends_with(".csv");
+    let sep = if csv { "," } else { "\t" };
+    let mut out = BufWriter::new(create_file(file)?);
+    let header = [
+        "file",
+        "reference",
+        "predicted",
+        "precision",
+        "recall",
+        "f_score",
+        "sn",
+        "ppv",
+        "acc",
+        "frac",
+        "sep",
+        "mmr",
+    ];
+    writeln!(out, "{}", header.join(sep))?;
+    for row in rows {
+        let s = &row.scores;
+        let mut fields = vec![
+            if csv {
+                csv_field(&row.file)
+            } else {
+                row.file.clone()
+            },
+            s.reference.to_string(),
+            s.predicted.to_string(),
+        ];
+        fields.extend(
+            [
+                s.precision,
+                s.recall,
+                s.f_score,
+                s.sn,
+                s.ppv,
+                s.acc,
+                s.frac,
+                s.sep,
+                s.mmr,
+            ]
+            .iter()
+            .map(|v| format!("{:.6}", v)),
+        );
+        writeln!(out, "{}", fields.join(sep))?;
+    }
+    out.flush()
 }
 
 // 泛型读取 complex 的函数
@@ -227,7 +848,11 @@ mod tests {
 
     use crate::{eva::COMPLEX_REF, graph::Graph};
 
-    use super::{confucion_matrix, read_complex, update_by_cohesion, Complex};
+    use super::{
+        confucion_matrix, evaluate, evaluate_files, match_report, maximum_matching, read_complex,
+        read_complexes, read_known_proteins, select_by_cohesion, update_by_cohesion, write_scores,
+        CohesionCut, Complex, OverlapStrategy, SelectOptions, Selection, THRESHOLD_OS,
+    };
 
     #[test]
     fn test_update_by_cohesion() {
@@ -239,6 +864,52 @@ mod tests {
         println!("{:?}", res);
     }
 
+    #[test]
+    fn test_select_by_cohesion_strategies() {
+        let complexes = vec![
+            Complex::new(vec![1, 2, 3, 4, 5], 0.8),
+            Complex::new(vec![1, 2, 3, 4], 0.9),
+            Complex::new(vec![4, 5, 6], 0.5),
+            Complex::new(vec![7, 8, 9], 0.7),
+        ];
+        let size = |p: &[i32]| p.len() as f64 / 10.;
+
+        let options = SelectOptions {
+            overlap: OverlapStrategy::Discard,
+            cut: CohesionCut::Keep,
+        };
+        let (res, selection) = select_by_cohesion(&complexes, &options, size);
+        assert_eq!(res.len(), 3);
+        assert_eq!(selection[0], Selection::Overlapped(1));
+
+        let options = SelectOptions {
+            overlap: OverlapStrategy::Merge,
+            cut: CohesionCut::Keep,
+        };
+        let (res, selection) = select_by_cohesion(&complexes, &options, size);
+        assert_eq!(res[0].proteins, vec![7, 8, 9]);
+        assert_eq!(res[1].proteins, vec![1, 2, 3, 4, 5]);
+        assert_eq!(selection[0], Selection::Merged(1));
+
+        let options = SelectOptions {
+            overlap: OverlapStrategy::ClusterOne(0.8),
+            cut: CohesionCut::Keep,
+        };
+        let (res, selection) = select_by_cohesion(&complexes, &options, size);
+        assert_eq!(res.len(), 3);
+        assert_eq!(selection[0], Selection::Merged(1));
+
+        // 4 与 5 个成员中共享 4 个，大小相关的阈值下仍视为重叠
+        let options = SelectOptions {
+            overlap: OverlapStrategy::SizeAware,
+            cut: CohesionCut::Percentile(50.),
+        };
+        let (res, selection) = select_by_cohesion(&complexes, &options, size);
+        assert_eq!(res.len(), 2);
+        assert_eq!(selection[0], Selection::Overlapped(1));
+        assert_eq!(selection[2], Selection::Truncated);
+    }
+
     #[test]
     fn test_complex() {
         let graph = Graph::new_from_file("./data/collins/collins.txt", false);
@@ -270,6 +941,152 @@ mod tests {
         confucion_matrix(refc, prec);
     }
 
+    #[test]
+    fn test_evaluate() {
+        let complex = |ps: &[&str]| Complex::new(ps.iter().map(|p| p.to_string()).collect(), 0.);
+        let refc = vec![complex(&["A", "B", "C"]), complex(&["B", "C", "D"])];
+        let prec = vec![complex(&["A", "B", "C"]), complex(&["C", "D", "E"])];
+        let scores = evaluate(&refc, &prec, THRESHOLD_OS);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-4;
+
+        assert_eq!((scores.reference, scores.predicted), (2, 2));
+        assert!(close(scores.precision, 1.) && close(scores.recall, 1.));
+        assert!(close(scores.f_score, 1.) && close(scores.frac, 1.));
+        assert!(close(scores.sn, 5. / 6.));
+        assert!(close(scores.ppv, 5. / 8.));
+        assert!(close(scores.acc, 0.7217));
+        assert!(close(scores.sep, 0.5333));
+        assert!(close(scores.mmr, 0.7222));
+
+        let matching = maximum_matching(&refc, &prec, THRESHOLD_OS);
+        assert_eq!(
+            matching
+                .pairs
+                .iter()
+                .map(|(i, j, _)| (*i, *j))
+                .collect::<Vec<_>>(),
+            vec![(0, 0), (1, 1)]
+        );
+
+        let empty = evaluate(&refc, &[], THRESHOLD_OS);
+        assert_eq!(empty.acc, 0.);
+        assert_eq!(empty.mmr, 0.);
+    }
+
+    #[test]
+    fn test_evaluate_collins() {
+        // 参考值由未修改的 evaluation/match.py（python2.7）在 collins 网络上计算得到；
+        // Frac 和 Sep 不在 match.py 中，对 match.py 读入的复合物按 ClusterONE 的定义计算
+        let known = read_known_proteins("./data/collins/collins.txt");
+        let refc = read_complexes(COMPLEX_REF, Some(&known));
+        let prec = read_complexes("./data/collins/collins_clique.txt", Some(&known));
+        let scores = evaluate(&refc, &prec, THRESHOLD_OS);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-4;
+
+        assert_eq!((scores.reference, scores.predicted), (396, 3030));
+        assert!(close(scores.precision, 0.159406));
+        assert!(close(scores.recall, 0.858586));
+        assert!(close(scores.f_score, 0.268890));
+        assert!(close(scores.sn, 0.728966));
+        assert!(close(scores.ppv, 0.238523));
+        assert!(close(scores.acc, 0.416983));
+        assert!(close(scores.frac, 0.838384));
+        assert!(close(scores.sep, 0.084881));
+        assert!(close(scores.mmr, 0.421321));
+    }
+
+    #[test]
+    fn test_match_report() {
+        let complex = |ps: &[&str]| Complex::new(ps.iter().map(|p| p.to_string()).collect(), 0.);
+        let refc = vec![
+            complex(&["A", "B", "C", "D"]),
+            complex(&["X", "Y", "Z"]),
+            complex(&["D", "E", "F"]),
+        ];
+        let prec = vec![
+            complex(&["A", "B", "C"]),
+            complex(&["D", "Q", "R"]),
+            complex(&["M", "N", "O"]),
+        ];
+        let report = match_report(&refc, &prec, THRESHOLD_OS);
+
+        let first = &report.references[0];
+        assert_eq!((first.best, first.matched), (Some(0), true));
+        assert_eq!(first.shared, vec!["A", "B", "C"]);
+        assert_eq!(first.missing, vec!["D"]);
+        assert_eq!(
+            (report.references[1].best, report.references[1].os),
+            (None, 0.)
+        );
+        // 重叠分数 1/9 不超过阈值
+        assert_eq!(report.references[2].best, Some(1));
+        assert!(!report.references[2].matched);
+
+        let matched = report
+            .predictions
+            .iter()
+            .map(|p| (p.best, p.matched))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matched,
+            vec![(Some(0), true), (Some(2), false), (None, false)]
+        );
+
+        let dir = std::env::temp_dir().join(format!("match_report_{}", std::process::id()));
+        let references = dir.join("references.tsv");
+        report
+            .write_references(references.to_str().unwrap(), &[])
+            .unwrap();
+        let references = std::fs::read_to_string(references).unwrap();
+        assert_eq!(
+            references.lines().nth(1),
+            Some("0\t0\t0.7500\tyes\tA,B,C\tD")
+        );
+        assert_eq!(references.lines().nth(2), Some("1\t-\t0.0000\tno\t\tX,Y,Z"));
+        let predictions = dir.join("predictions.tsv");
+        report
+            .write_predictions(predictions.to_str().unwrap(), &[])
+            .unwrap();
+        let predictions = std::fs::read_to_string(predictions).unwrap();
+        assert_eq!(predictions.lines().nth(2), Some("1\tnovel\t0.1111"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_evaluate_files() {
+        let complex = |ps: &[&str]| Complex::new(ps.iter().map(|p| p.to_string()).collect(), 0.);
+        let refc = vec![complex(&["A", "B", "D"]), complex(&["B", "D", "E"])];
+        let known = ["A", "B", "D", "E", "F"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<HashSet<_>>();
+
+        let dir = std::env::temp_dir().join(format!("eva_files_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files = [("a,1.txt", "A\tB\tF\n"), ("b.txt", "a\tb\td\nD\tE\tF\n")]
+            .iter()
+            .map(|(name, contents)| {
+                let file = dir.join(name);
+                std::fs::write(&file, contents).unwrap();
+                file.to_str().unwrap().to_string()
+            })
+            .collect::<Vec<_>>();
+
+        let rows = evaluate_files(&refc, &files, &known, "acc".parse().unwrap());
+        assert_eq!(rows[0].file, files[1]);
+        assert_eq!(rows[0].scores.predicted, 2);
+        assert!(rows[0].scores.acc > rows[1].scores.acc);
+
+        let table = dir.join("scores.csv");
+        write_scores(table.to_str().unwrap(), &rows).unwrap();
+        let table = std::fs::read_to_string(table).unwrap();
+        assert_eq!(table.lines().count(), 3);
+        assert!(table.starts_with("file,reference,predicted,precision"));
+        // 含逗号的文件名加引号
+        assert!(table.contains(&format!("\n\"{}\",2,", files[0])));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
     #[test]
     fn test_confusion_matrix2() {
         let graph = Graph::new_from_file("./data/collins/collins.txt", false);
@@ -1,12 +1,168 @@
 /// 主要写基因表达相关逻辑
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fs::read_to_string,
 };
 
-use crate::graph::Graph;
+use crate::{graph::Graph, stats::percentile};
+
+pub const DEFAULT_EXPRESSION_FILE: &str = "./data/gene-expression.txt";
+pub const DEFAULT_ESSENTIAL_FILE: &str = "./data/essential proteins.ref";
+// k-means 的最大迭代次数
+const KMEANS_ITER: usize = 100;
+/// 酵母代谢周期数据（Tu et al.）每个周期的时相数，周期数为列数除以时相数；
+/// 自带的 gene-expression.txt 是18个恒化培养条件，不是周期性数据，不应折叠
+pub const METABOLIC_CYCLE_PHASES: usize = 12;
+
+// 判断蛋白质在某个时间点是否活跃的模型
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ActivityModel {
+    // Wang et al. 三倍标准差: mean + 3 * sigma * (1 - F)
+    ThreeSigma,
+    // 关键蛋白质与非关键蛋白质使用不同的阈值
+    #[default]
+    Edpin,
+    // 表达值不低于自身表达谱的该百分位数（0-100）时活跃
+    Percentile(f64),
+    // TS-PIN，每个时间点使用该时间点所有蛋白质表达值的3-sigma阈值
+    TsPin,
+    // 对表达谱做一维2-means聚类，高表达簇为活跃
+    KMeans,
+}
+
+// 没有基因表达谱的蛋白质的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MissingProfile {
+    // 在所有子网络中保留
+    #[default]
+    KeepAll,
+    // 在所有子网络中删除
+    DropAll,
+    // 仅在有活跃邻居的时间点保留
+    KeepIfNeighborActive,
+}
+
+// 表达矩阵中缺失值的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MissingValue {
+    // 用该蛋白质已观测值的均值填充
+    #[default]
+    RowMean,
+    // 用前后最近的观测值线性插值，两端取最近的观测值
+    Interpolate,
+    // 用常数填充
+    Fill(f64),
+    // 保留为NaN，计算均值方差时跳过，该时间点视为不活跃
+    Mask,
+    // 删除含缺失值的蛋白质
+    Drop,
+}
+
+// 两个表达谱之间的共表达度量
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoExpression {
+    Pearson,
+    Spearman,
+    // 滞后不超过该步数时 Pearson 相关系数的最大值（两个方向）
+    TimeLagged(usize),
+    // 等宽分箱估计的归一化互信息，参数为分箱数
+    MutualInformation(usize),
+}
+
+impl CoExpression {
+    // 原始的度量值，相关系数在[-1, 1]，互信息在[0, 1]
+    // 只使用两个表达谱都有观测值的时间点，观测值不足时为 None
+    pub fn measure(&self, a: &[f64], b: &[f64]) -> Option<f64> {
+        if let CoExpression::TimeLagged(max_lag) = self {
+            // 先在原始表达谱上平移，再去掉含缺失值的时间点对，
+            // 保证滞后对应的是真实的时间间隔
+            let len = a.len().min(b.len());
+            return (0..=*max_lag)
+                .filter(|lag| lag + 3 <= len)
+                .flat_map(|lag| {
+                    let n = len - lag;
+                    [
+                        co_observed(&a[..n], &b[lag..len]),
+                        co_observed(&b[..n], &a[lag..len]),
+                    ]
+                })
+                .filter(|(x, _)| x.len() >= 3)
+                .map(|(x, y)| pearson(&x, &y))
+                .reduce(f64::max);
+        }
+
+        let (a, b) = co_observed(a, b);
+        if a.len() < 3 {
+            return None;
+        }
+
+        let value = match self {
+            CoExpression::Pearson => pearson(&a, &b),
+            CoExpression::Spearman => pearson(&ranks(&a), &ranks(&b)),
+            CoExpression::TimeLagged(_) => unreachable!(),
+            CoExpression::MutualInformation(bins) => mutual_information(&a, &b, *bins),
+        };
+        Some(value)
+    }
+
+    // 映射到[0, 1]的相似性，相关系数r映射为 (1 + r) / 2
+    pub fn similarity(&self, a: &[f64], b: &[f64]) -> Option<f64> {
+        let value = self.measure(a, b)?;
+        Some(match self {
+            CoExpression::MutualInformation(_) => value,
+            _ => (1. + value) / 2.,
+        })
+    }
+}
+
+// 同一时相的多个时间点的合并方式
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PhaseCombine {
+    #[default]
+    Mean,
+    Max,
+}
+
+// 将周期性的时间点折叠为时相，第i列属于第 i % phases 个时相
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhaseFolding {
+    pub phases: usize,
+    pub combine: PhaseCombine,
+}
+
+impl PhaseFolding {
+    pub fn new(phases: usize, combine: PhaseCombine) -> Self {
+        Self { phases, combine }
+    }
+
+    // 每个时相包含的列，没有列的时相被忽略
+    pub fn groups(&self, count: usize) -> Vec<Vec<usize>> {
+        (0..self.phases.max(1))
+            .map(|p| (p..count).step_by(self.phases.max(1)).collect::<Vec<_>>())
+            .filter(|g| !g.is_empty())
+            .collect()
+    }
+}
+
+// 一个动态子网络
+#[derive(Debug)]
+pub struct Dpin {
+    // 时间点的列名或时相
+    pub label: String,
+    // 活跃的蛋白质
+    pub active: HashSet<String>,
+    pub graph: Graph,
+}
+
+pub struct ExpressionMatrix {
+    // 条件/时间点的列名
+    pub columns: Vec<String>,
+    pub profiles: HashMap<String, Vec<f64>>,
+}
 
 pub struct GeneExpress {
+    // 条件/时间点的列名
+    pub(crate) columns: Vec<String>,
     // 蛋白质的基因表达谱
     pub(crate) express: HashMap<String, Vec<f64>>,
     // 表达谱对应的均值和方差
@@ -16,7 +172,15 @@ pub struct GeneExpress {
 impl GeneExpress {
     // 读取蛋白质的基因表达谱而非所有
     pub fn new(file: &str, proteins: &HashSet<String>) -> Self {
-        let express = read_gene_expression(file, proteins);
+        Self::new_with(file, proteins, MissingValue::default())
+    }
+
+    pub fn new_with(file: &str, proteins: &HashSet<String>, missing: MissingValue) -> Self {
+        let ExpressionMatrix { columns, profiles } = read_expression_matrix(file, missing);
+        let express = profiles
+            .into_iter()
+            .filter(|(p, _)| proteins.contains(p))
+            .collect::<HashMap<_, _>>();
         let mean_variance = express
             .iter()
             .map(|(a, b)| {
@@ -26,99 +190,561 @@ impl GeneExpress {
             .collect::<HashMap<_, _>>();
 
         Self {
+            columns,
             express,
             mean_variance,
         }
     }
 
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    // 两个蛋白质的共表达相似性，任一蛋白质没有表达谱时为 None
+    pub fn coexpression(&self, a: &str, b: &str, measure: CoExpression) -> Option<f64> {
+        measure.similarity(self.express.get(a)?, self.express.get(b)?)
+    }
+
+    // 两个蛋白质表达谱的相关系数（未映射到[0, 1]），任一蛋白质没有表达谱时为 None
+    pub fn correlation(&self, a: &str, b: &str, measure: CoExpression) -> Option<f64> {
+        measure.measure(self.express.get(a)?, self.express.get(b)?)
+    }
+
     // EDPIN，为关键蛋白质和非关键蛋白质设置不同的基因活性阈值
     pub fn calculate_active_threshold(&self, eps: &HashSet<String>) -> HashMap<String, f64> {
         let mut threshold = HashMap::<String, f64>::new();
 
         self.mean_variance.iter().for_each(|(a, (mean, variance))| {
-            let f = variance / (1.0 + variance);
             let k = match eps.contains(a) {
                 false => 1.,
                 true => -1.,
             };
-            let active = mean + k * variance.sqrt() * f;
-            threshold.insert(a.clone(), active);
+            threshold.insert(a.clone(), sigma_threshold(*mean, *variance, k));
         });
 
         threshold
     }
 
-    fn count(&self) -> usize {
-        let mut count = 0;
-        for i in self.express.iter() {
-            count = i.1.len();
-            break;
+    // 每个蛋白质在各时间点是否活跃
+    pub fn activity(
+        &self,
+        model: ActivityModel,
+        eps: &HashSet<String>,
+    ) -> HashMap<String, Vec<bool>> {
+        let by_threshold = |threshold: &HashMap<String, f64>| {
+            self.express
+                .iter()
+                .map(|(p, exp)| (p.clone(), exp.iter().map(|v| v.ge(&threshold[p])).collect()))
+                .collect()
+        };
+
+        match model {
+            ActivityModel::ThreeSigma => {
+                let threshold = self
+                    .mean_variance
+                    .iter()
+                    .map(|(p, (mean, variance))| (p.clone(), sigma_threshold(*mean, *variance, 3.)))
+                    .collect();
+                by_threshold(&threshold)
+            }
+            ActivityModel::Edpin => by_threshold(&self.calculate_active_threshold(eps)),
+            ActivityModel::Percentile(q) => {
+                let threshold = self
+                    .express
+                    .iter()
+                    .map(|(p, exp)| (p.clone(), percentile(&observed(exp), q)))
+                    .collect();
+                by_threshold(&threshold)
+            }
+            ActivityModel::TsPin => {
+                let threshold = (0..self.count())
+                    .map(|t| {
+                        let column = self.express.values().map(|e| e[t]);
+                        let column = observed(&column.collect::<Vec<_>>());
+                        if column.is_empty() {
+                            return f64::INFINITY;
+                        }
+                        let (mean, variance) = get_mean_variance(&column);
+                        sigma_threshold(mean, variance, 3.)
+                    })
+                    .collect::<Vec<_>>();
+                self.express
+                    .iter()
+                    .map(|(p, exp)| {
+                        let active = exp.iter().zip(threshold.iter()).map(|(v, t)| v.ge(t));
+                        (p.clone(), active.collect())
+                    })
+                    .collect()
+            }
+            ActivityModel::KMeans => self
+                .express
+                .iter()
+                .map(|(p, exp)| (p.clone(), kmeans_active(exp)))
+                .collect(),
         }
-        count
+    }
+
+    // 按时相合并时间点，列名为 "phase i"
+    pub fn fold_phases(&self, folding: &PhaseFolding) -> Self {
+        let groups = folding.groups(self.count());
+        let express = self
+            .express
+            .iter()
+            .map(|(p, exp)| {
+                let folded = groups
+                    .iter()
+                    .map(|g| {
+                        let values = observed(&g.iter().map(|i| exp[*i]).collect::<Vec<_>>());
+                        match (values.is_empty(), folding.combine) {
+                            (true, _) => f64::NAN,
+                            (false, PhaseCombine::Mean) => {
+                                values.iter().sum::<f64>() / values.len() as f64
+                            }
+                            (false, PhaseCombine::Max) => {
+                                values.into_iter().fold(f64::NEG_INFINITY, f64::max)
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                (p.clone(), folded)
+            })
+            .collect::<HashMap<_, _>>();
+        let mean_variance = express
+            .iter()
+            .map(|(p, exp)| (p.clone(), get_mean_variance(exp)))
+            .collect();
+
+        Self {
+            columns: (0..groups.len()).map(|i| format!("phase {}", i)).collect(),
+            express,
+            mean_variance,
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.columns.len()
     }
 }
 
-pub fn get_dpins(g: &Graph) -> Vec<Graph> {
-    let proteins = g.id_protein.iter().map(|c| c.to_string()).collect();
-    let gep = GeneExpress::new("./data/gene-expression.txt", &proteins);
-    let eps = read_essential_protein();
-    // 计算蛋白质的活跃值
-    let active = gep.calculate_active_threshold(&eps);
-
-    // 更新节点
-    let mut nodes = vec![HashSet::<String>::new(); gep.count()];
-    for p in proteins.iter() {
-        // 不含有该蛋白质的基因表达
-        if let Some(exp) = gep.express.get(p) {
-            for (i, value) in exp.iter().enumerate() {
-                if value.ge(active.get(p).unwrap()) {
-                    nodes[i].insert(p.clone());
+#[derive(Debug, Clone)]
+pub struct DpinBuilder {
+    // 基因表达文件
+    file: String,
+    // 关键蛋白质，EDPIN 使用
+    essential: HashSet<String>,
+    model: ActivityModel,
+    missing: MissingProfile,
+    // 表达矩阵中缺失值的处理
+    missing_value: MissingValue,
+    // 按周期时相折叠时间点，None 时每个时间点一个子网络
+    phases: Option<PhaseFolding>,
+}
+
+impl DpinBuilder {
+    pub fn new(file: &str) -> Self {
+        Self {
+            file: file.to_string(),
+            essential: HashSet::new(),
+            model: ActivityModel::default(),
+            missing: MissingProfile::default(),
+            missing_value: MissingValue::default(),
+            phases: None,
+        }
+    }
+
+    pub fn essential(mut self, essential: HashSet<String>) -> Self {
+        self.essential = essential;
+        self
+    }
+
+    pub fn model(mut self, model: ActivityModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn missing(mut self, missing: MissingProfile) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    pub fn missing_value(mut self, missing_value: MissingValue) -> Self {
+        self.missing_value = missing_value;
+        self
+    }
+
+    pub fn fold_phases(mut self, phases: usize, combine: PhaseCombine) -> Self {
+        self.phases = Some(PhaseFolding::new(phases, combine));
+        self
+    }
+
+    // 每个时间点活跃的蛋白质
+    pub fn active_sets(&self, g: &Graph) -> Vec<HashSet<String>> {
+        self.labeled_active_sets(g).1
+    }
+
+    // 读取网络中蛋白质的基因表达谱，设置了时相时返回折叠后的表达谱
+    pub fn express(&self, g: &Graph) -> GeneExpress {
+        let proteins = g.id_protein.iter().map(|c| c.to_string()).collect();
+        let gep = GeneExpress::new_with(&self.file, &proteins, self.missing_value);
+        match &self.phases {
+            Some(folding) => gep.fold_phases(folding),
+            None => gep,
+        }
+    }
+
+    // 按活跃模型判断蛋白质在各时间点是否活跃
+    pub fn activity(&self, gep: &GeneExpress) -> HashMap<String, Vec<bool>> {
+        gep.activity(self.model, &self.essential)
+    }
+
+    // 时间点的列名（或时相）以及每个时间点活跃的蛋白质
+    pub fn labeled_active_sets(&self, g: &Graph) -> (Vec<String>, Vec<HashSet<String>>) {
+        let gep = self.express(g);
+        let activity = self.activity(&gep);
+        let is_active = |p: &str, t: usize| activity.get(p).is_some_and(|a| a[t]);
+
+        let mut nodes = vec![HashSet::<String>::new(); gep.count()];
+        for (id, p) in g.id_protein.iter().enumerate() {
+            if let Some(active) = activity.get(p) {
+                for (t, _) in active.iter().enumerate().filter(|(_, a)| **a) {
+                    nodes[t].insert(p.clone());
+                }
+                continue;
+            }
+            // 不含有该蛋白质的基因表达
+            for (t, c) in nodes.iter_mut().enumerate() {
+                let keep = match self.missing {
+                    MissingProfile::KeepAll => true,
+                    MissingProfile::DropAll => false,
+                    MissingProfile::KeepIfNeighborActive => g.nei_list[id]
+                        .keys()
+                        .any(|j| is_active(&g.id_protein[*j], t)),
+                };
+                if keep {
+                    c.insert(p.clone());
                 }
             }
-        } else {
-            // 在所有子网络中保留
-            nodes.iter_mut().for_each(|c| {
-                c.insert(p.clone());
-            });
         }
+        (gep.columns, nodes)
+    }
+
+    pub fn build(&self, g: &Graph) -> Vec<Graph> {
+        self.active_sets(g)
+            .into_iter()
+            .map(|nodes| g.subgraph(&nodes))
+            .collect()
+    }
+
+    // 带有标签和活跃蛋白质集合的动态子网络
+    pub fn build_dpins(&self, g: &Graph) -> Vec<Dpin> {
+        let (columns, sets) = self.labeled_active_sets(g);
+        columns
+            .into_iter()
+            .zip(sets)
+            .map(|(label, active)| Dpin {
+                label,
+                graph: g.subgraph(&active),
+                active,
+            })
+            .collect()
     }
+}
 
-    nodes.into_iter().map(|nodes| g.subgraph(&nodes)).collect()
+// 删除两端蛋白质从未同时活跃的边，任一端没有表达谱的边保留，返回删除的边数
+pub fn remove_never_coactive(g: &mut Graph, activity: &HashMap<String, Vec<bool>>) -> usize {
+    let mut edge_remove = Vec::new();
+    g.nei_list.iter().enumerate().for_each(|(a, nei)| {
+        nei.keys().filter(|b| a < **b).for_each(|b| {
+            let pa = activity.get(&g.id_protein[a]);
+            let pb = activity.get(&g.id_protein[*b]);
+            if let (Some(pa), Some(pb)) = (pa, pb) {
+                if !pa.iter().zip(pb.iter()).any(|(x, y)| *x && *y) {
+                    edge_remove.push((a, *b));
+                }
+            }
+        });
+    });
+
+    edge_remove.iter().for_each(|(a, b)| g.remove_edge(*a, *b));
+    edge_remove.len()
+}
+
+pub fn get_dpins(g: &Graph) -> Vec<Graph> {
+    DpinBuilder::new(DEFAULT_EXPRESSION_FILE)
+        .essential(read_essential_protein())
+        .build(g)
 }
 
 pub fn read_essential_protein() -> HashSet<String> {
-    let lines = read_to_string("./data/essential proteins.ref")
-        .expect("Failed to read essential protein file!");
+    let lines =
+        read_to_string(DEFAULT_ESSENTIAL_FILE).expect("Failed to read essential protein file!");
     lines.lines().map(|c| c.to_string()).collect()
 }
 
-fn read_gene_expression(file: &str, eps: &HashSet<String>) -> HashMap<String, Vec<f64>> {
-    let lines = read_to_string(file)
-        .expect("Failed to read gene expression file!")
+// 读取基因表达矩阵，支持 YORF/NAME/GWEIGHT/EWEIGHT 格式以及
+// 首列为ORF、其余列为表达值的普通矩阵（表头可有可无）
+// 空值、NA、NaN 以及无法解析的值视为缺失值，同一ORF的多行按列取均值
+pub fn read_expression_matrix(file: &str, missing: MissingValue) -> ExpressionMatrix {
+    let contents = read_to_string(file).expect("Failed to read gene expression file!");
+    let mut lines = contents
         .lines()
-        .map(|s| s.to_owned())
-        .collect::<Vec<_>>();
+        .filter(|l| !l.trim().is_empty())
+        .map(split_cells)
+        .peekable();
+
+    let header = lines.peek().cloned().unwrap_or_default();
+    let gweight = header
+        .iter()
+        .position(|c| c.eq_ignore_ascii_case("GWEIGHT"));
+    // 首行除首列外都是数值时没有表头
+    let has_header = gweight.is_some()
+        || !header
+            .iter()
+            .skip(1)
+            .all(|c| c.parse::<f64>().is_ok() || is_missing(c));
+    // 表达值开始的列
+    let start = gweight.map_or(1, |i| i + 1);
+    let columns = match has_header {
+        true => header[start..].iter().map(|c| c.to_string()).collect(),
+        false => (0..header.len().saturating_sub(1))
+            .map(|i| format!("t{}", i))
+            .collect::<Vec<_>>(),
+    };
+    if has_header {
+        lines.next();
+    }
 
-    let mut protein_express = HashMap::<String, Vec<f64>>::new();
+    // 同一ORF的多行: (各列之和, 各列观测次数)
+    let mut rows = BTreeMap::<String, (Vec<f64>, Vec<usize>)>::new();
     for line in lines {
-        let line = line.split_whitespace().collect::<Vec<_>>();
-        let protein = line[0].to_owned();
-        if !eps.contains(&protein) {
+        if line[0].eq_ignore_ascii_case("EWEIGHT") {
             continue;
         }
-        let exp = line[3..]
-            .into_iter()
-            .map(|c| c.parse::<f64>().unwrap())
-            .collect::<Vec<_>>();
-        protein_express.insert(protein, exp);
+        let (sum, count) = rows
+            .entry(line[0].to_string())
+            .or_insert_with(|| (vec![0.; columns.len()], vec![0; columns.len()]));
+        for (i, cell) in line.iter().skip(start).take(columns.len()).enumerate() {
+            if let Ok(v) = cell.parse::<f64>() {
+                if v.is_finite() {
+                    sum[i] += v;
+                    count[i] += 1;
+                }
+            }
+        }
+    }
+
+    let profiles = rows
+        .into_iter()
+        .filter_map(|(orf, (sum, count))| {
+            let exp = sum
+                .iter()
+                .zip(count.iter())
+                .map(|(s, c)| if *c == 0 { f64::NAN } else { s / *c as f64 })
+                .collect::<Vec<_>>();
+            impute(exp, missing).map(|exp| (orf, exp))
+        })
+        .collect();
+
+    ExpressionMatrix { columns, profiles }
+}
+
+// 有制表符时按制表符分割以保留空值，否则按空白分割
+fn split_cells(line: &str) -> Vec<&str> {
+    if line.contains('\t') {
+        line.split('\t').map(|c| c.trim()).collect()
+    } else {
+        line.split_whitespace().collect()
     }
+}
 
-    protein_express
+fn is_missing(cell: &str) -> bool {
+    matches!(
+        cell.to_ascii_uppercase().as_str(),
+        "" | "NA" | "N/A" | "NAN" | "NULL" | "-"
+    )
+}
+
+// 按策略处理缺失值，没有任何观测值或策略为 Drop 时返回 None
+fn impute(mut exp: Vec<f64>, missing: MissingValue) -> Option<Vec<f64>> {
+    let known = exp
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| !v.is_nan())
+        .map(|(i, v)| (i, *v))
+        .collect::<Vec<_>>();
+    if known.is_empty() {
+        return None;
+    }
+    if known.len() == exp.len() {
+        return Some(exp);
+    }
+
+    match missing {
+        MissingValue::RowMean => {
+            let mean = known.iter().map(|(_, v)| v).sum::<f64>() / known.len() as f64;
+            exp.iter_mut()
+                .filter(|v| v.is_nan())
+                .for_each(|v| *v = mean);
+        }
+        MissingValue::Interpolate => {
+            for (i, v) in exp.iter_mut().enumerate().filter(|(_, v)| v.is_nan()) {
+                let before = known.iter().rev().find(|(j, _)| *j < i);
+                let after = known.iter().find(|(j, _)| *j > i);
+                *v = match (before, after) {
+                    (Some((a, va)), Some((b, vb))) => {
+                        va + (vb - va) * (i - a) as f64 / (b - a) as f64
+                    }
+                    (Some((_, va)), None) => *va,
+                    (None, Some((_, vb))) => *vb,
+                    (None, None) => unreachable!("known is not empty"),
+                };
+            }
+        }
+        MissingValue::Fill(value) => {
+            exp.iter_mut()
+                .filter(|v| v.is_nan())
+                .for_each(|v| *v = value);
+        }
+        MissingValue::Mask => {}
+        MissingValue::Drop => return None,
+    }
+    Some(exp)
+}
+
+fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let (ma, mb) = (a.iter().sum::<f64>() / n, b.iter().sum::<f64>() / n);
+    let (mut cov, mut va, mut vb) = (0., 0., 0.);
+    for (x, y) in a.iter().zip(b.iter()) {
+        cov += (x - ma) * (y - mb);
+        va += (x - ma).powi(2);
+        vb += (y - mb).powi(2);
+    }
+    // 表达值没有变化时视为不相关
+    if va <= 0. || vb <= 0. {
+        return 0.;
+    }
+    cov / (va * vb).sqrt()
+}
+
+// 秩，相同的值取平均秩
+fn ranks(data: &[f64]) -> Vec<f64> {
+    let mut order = (0..data.len()).collect::<Vec<_>>();
+    order.sort_by(|i, j| data[*i].total_cmp(&data[*j]));
+    let mut ranks = vec![0.; data.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && data[order[j + 1]] == data[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2. + 1.;
+        order[i..=j].iter().for_each(|k| ranks[*k] = rank);
+        i = j + 1;
+    }
+    ranks
+}
+
+// 两个表达谱都有观测值的时间点
+fn co_observed(a: &[f64], b: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    a.iter()
+        .zip(b.iter())
+        .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+        .unzip()
+}
+
+// 等宽分箱，归一化为 I(a; b) / sqrt(H(a) * H(b))
+fn mutual_information(a: &[f64], b: &[f64], bins: usize) -> f64 {
+    let bins = bins.max(2);
+    let bin_of = |data: &[f64]| {
+        let lo = data.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        data.iter()
+            .map(|v| match hi > lo {
+                true => (((v - lo) / (hi - lo) * bins as f64) as usize).min(bins - 1),
+                false => 0,
+            })
+            .collect::<Vec<_>>()
+    };
+    let (xa, xb) = (bin_of(a), bin_of(b));
+
+    let n = a.len() as f64;
+    let mut joint = vec![0.; bins * bins];
+    let (mut pa, mut pb) = (vec![0.; bins], vec![0.; bins]);
+    for (x, y) in xa.iter().zip(xb.iter()) {
+        joint[x * bins + y] += 1. / n;
+        pa[*x] += 1. / n;
+        pb[*y] += 1. / n;
+    }
+
+    let entropy = |p: &[f64]| {
+        -p.iter()
+            .filter(|v| **v > 0.)
+            .map(|v| v * v.ln())
+            .sum::<f64>()
+    };
+    let (ha, hb) = (entropy(&pa), entropy(&pb));
+    if ha <= f64::EPSILON || hb <= f64::EPSILON {
+        return 0.;
+    }
+    let mi = joint
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| **p > 0.)
+        .map(|(i, p)| p * (p / (pa[i / bins] * pb[i % bins])).ln())
+        .sum::<f64>();
+    (mi / (ha * hb).sqrt()).clamp(0., 1.)
+}
+
+// 去掉被屏蔽的缺失值
+fn observed(data: &[f64]) -> Vec<f64> {
+    data.iter().copied().filter(|v| !v.is_nan()).collect()
+}
+
+// mean + k * sigma * (1 - F)，其中 F = 1 / (1 + variance)
+fn sigma_threshold(mean: f64, variance: f64, k: f64) -> f64 {
+    let f = variance / (1.0 + variance);
+    mean + k * variance.sqrt() * f
+}
+
+// 一维2-means，以最小值和最大值为初始中心，属于高表达簇的时间点为活跃
+fn kmeans_active(data: &[f64]) -> Vec<bool> {
+    let lo = data.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    // 表达值没有变化
+    if lo.ge(&hi) {
+        return vec![true; data.len()];
+    }
+
+    let (mut lo, mut hi) = (lo, hi);
+    let mut active = Vec::new();
+    for _ in 0..KMEANS_ITER {
+        active = data
+            .iter()
+            .map(|v| (v - hi).abs() < (v - lo).abs())
+            .collect::<Vec<_>>();
+        let mean = |flag: bool| {
+            let values = data
+                .iter()
+                .zip(active.iter())
+                .filter(|(v, a)| **a == flag && !v.is_nan())
+                .map(|(v, _)| *v)
+                .collect::<Vec<_>>();
+            (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+        };
+        let (new_lo, new_hi) = (mean(false).unwrap_or(lo), mean(true).unwrap_or(hi));
+        if new_lo == lo && new_hi == hi {
+            break;
+        }
+        (lo, hi) = (new_lo, new_hi);
+    }
+    active
 }
 
 // 计算均值和方差
 fn get_mean_variance(data: &[f64]) -> (f64, f64) {
-    assert!(data.len() != 0);
+    let data = observed(data);
+    assert!(!data.is_empty());
 
     let sum: f64 = data.iter().sum();
     let mean = sum / data.len() as f64;
@@ -137,9 +763,183 @@ fn get_mean_variance(data: &[f64]) -> (f64, f64) {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use crate::graph::Graph;
 
-    use super::get_dpins;
+    use super::{
+        get_dpins, read_expression_matrix, remove_never_coactive, ActivityModel, CoExpression,
+        DpinBuilder, MissingProfile, MissingValue, PhaseCombine,
+    };
+
+    fn set(proteins: &[&str]) -> HashSet<String> {
+        proteins.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_dpin_builder() {
+        let file = std::env::temp_dir().join(format!("dpin_builder_{}.txt", std::process::id()));
+        std::fs::write(
+            &file,
+            "YORF\tNAME\tGWEIGHT\tt0\tt1\tt2\tt3\n\
+             EWEIGHT\t\t\t1\t1\t1\t1\n\
+             A\tA\t1\t1\t1\t5\t5\n\
+             B\tB\t1\t2\t2\t2\t2\n\
+             C\tC\t1\t0\t4\t0\t4\n",
+        )
+        .unwrap();
+        let mut g = Graph::new_from(vec![(0, 1, 1.), (1, 2, 1.), (0, 2, 1.), (2, 3, 1.)]);
+        g.id_protein = ["A", "B", "C", "D"].iter().map(|p| p.to_string()).collect();
+
+        let builder = DpinBuilder::new(file.to_str().unwrap()).essential(set(&["C"]));
+        let sets = builder.active_sets(&g);
+        assert_eq!(sets[0], set(&["B", "D"]));
+        assert_eq!(sets[3], set(&["A", "B", "C", "D"]));
+
+        let builder = builder.missing(MissingProfile::KeepIfNeighborActive);
+        let sets = builder.active_sets(&g);
+        assert_eq!(sets[0], set(&["B"]));
+        assert_eq!(sets[1], set(&["B", "C", "D"]));
+
+        let builder = builder.missing(MissingProfile::DropAll);
+        let sets = builder
+            .clone()
+            .model(ActivityModel::ThreeSigma)
+            .active_sets(&g);
+        assert_eq!(sets[2], set(&["B"]));
+        let sets = builder.clone().model(ActivityModel::KMeans).active_sets(&g);
+        assert_eq!(sets[2], set(&["A", "B"]));
+        let sets = builder
+            .clone()
+            .model(ActivityModel::Percentile(50.))
+            .active_sets(&g);
+        assert_eq!(sets[0], set(&["A", "B", "C"]));
+        let sets = builder.clone().model(ActivityModel::TsPin).active_sets(&g);
+        assert_eq!(sets[0], set(&["B"]));
+
+        let dpins = builder.build_dpins(&g);
+        assert_eq!(dpins.len(), 4);
+        assert_eq!(dpins[3].label, "t3");
+        assert_eq!(dpins[3].graph.node_count, 3);
+
+        // 每2个时间点为一个周期: (t0, t2), (t1, t3)
+        let builder = builder.model(ActivityModel::Edpin);
+        let dpins = builder
+            .clone()
+            .fold_phases(2, PhaseCombine::Max)
+            .build_dpins(&g);
+        assert_eq!(dpins.len(), 2);
+        assert_eq!(dpins[1].label, "phase 1");
+        assert_eq!(dpins[1].active, set(&["A", "B", "C"]));
+        assert_eq!(dpins[1].graph.node_count, 3);
+        let sets = builder.fold_phases(2, PhaseCombine::Mean).active_sets(&g);
+        assert_eq!(sets[0], set(&["A", "B"]));
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn test_coexpression() {
+        let close = |v: Option<f64>, e: f64| (v.unwrap() - e).abs() < 1e-9;
+        let a = [1., 2., 3., 4., 5.];
+        assert!(close(
+            CoExpression::Pearson.measure(&a, &[2., 4., 6., 8., 10.]),
+            1.
+        ));
+        assert!(close(
+            CoExpression::Pearson.similarity(&a, &[5., 4., 3., 2., 1.]),
+            0.
+        ));
+        assert!(close(
+            CoExpression::Spearman.measure(&a, &[1., 4., 9., 16., 25.]),
+            1.
+        ));
+        assert!(close(CoExpression::Pearson.measure(&a, &[1.; 5]), 0.));
+
+        // b 比 a 滞后一个时间点
+        let a = [1., 2., 3., 2., 1., 2., 3.];
+        let b = [0., 1., 2., 3., 2., 1., 2.];
+        assert!(CoExpression::Pearson.measure(&a, &b).unwrap() < 1.);
+        assert!(close(CoExpression::TimeLagged(1).measure(&a, &b), 1.));
+
+        let mi = CoExpression::MutualInformation(3);
+        assert!(close(mi.measure(&a, &a), 1.));
+        assert!(close(mi.measure(&a, &[1.; 7]), 0.));
+
+        // 缺失值所在的时间点被跳过
+        let b = [1., f64::NAN, 3., f64::NAN, 1., f64::NAN, 3.];
+        assert!(close(CoExpression::Pearson.measure(&a, &b), 1.));
+        assert!(CoExpression::Pearson.measure(&a[..2], &b[..2]).is_none());
+
+        // 平移在缺失值过滤之前进行，滞后仍对应真实的时间间隔
+        let a = [1., 2., 3., 2., 1., 2., 3., 2.];
+        let b = [0., 1., f64::NAN, 3., 2., 1., 2., 3.];
+        assert!(close(CoExpression::TimeLagged(1).measure(&a, &b), 1.));
+        // 滞后超过表达谱长度时不会溢出
+        let lagged = CoExpression::TimeLagged(5);
+        assert!(lagged.measure(&a[..4], &b[..4]).is_some());
+        assert!(lagged.measure(&a[..2], &b[..2]).is_none());
+    }
+
+    #[test]
+    fn test_remove_never_coactive() {
+        let mut g = Graph::new_from(vec![(0, 1, 1.), (1, 2, 1.), (2, 3, 1.)]);
+        g.id_protein = ["A", "B", "C", "D"].iter().map(|p| p.to_string()).collect();
+        let activity = [
+            ("A", vec![true, false]),
+            ("B", vec![true, true]),
+            ("C", vec![false, false]),
+        ]
+        .into_iter()
+        .map(|(p, a)| (p.to_string(), a))
+        .collect();
+
+        assert_eq!(remove_never_coactive(&mut g, &activity), 1);
+        assert!(g.has_edge(0, 1));
+        assert!(!g.has_edge(1, 2));
+        // D 没有表达谱
+        assert!(g.has_edge(2, 3));
+    }
+
+    #[test]
+    fn test_read_expression_matrix() {
+        let dir = std::env::temp_dir();
+        let file = dir.join(format!("expression_{}.txt", std::process::id()));
+        std::fs::write(
+            &file,
+            "YORF\tNAME\tGWEIGHT\tcond 1\tcond 2\tcond 3\n\
+             EWEIGHT\t\t\t1\t1\t1\n\
+             A\tA\t1\t1\t\t3\n\
+             B\tB\t1\t2\tNA\tNA\n\
+             B\tB\t1\t4\t6\tNA\n\
+             C\tC\t1\tNA\tNA\tNA\n",
+        )
+        .unwrap();
+        let file = file.to_str().unwrap();
+
+        let m = read_expression_matrix(file, MissingValue::RowMean);
+        assert_eq!(m.columns, vec!["cond 1", "cond 2", "cond 3"]);
+        assert_eq!(m.profiles.len(), 2);
+        assert_eq!(m.profiles["A"], vec![1., 2., 3.]);
+        assert_eq!(m.profiles["B"], vec![3., 6., 4.5]);
+
+        let m = read_expression_matrix(file, MissingValue::Interpolate);
+        assert_eq!(m.profiles["A"], vec![1., 2., 3.]);
+        assert_eq!(m.profiles["B"], vec![3., 6., 6.]);
+
+        let m = read_expression_matrix(file, MissingValue::Mask);
+        assert!(m.profiles["A"][1].is_nan());
+        let m = read_expression_matrix(file, MissingValue::Drop);
+        assert!(m.profiles.is_empty());
+        std::fs::remove_file(file).unwrap();
+
+        // 没有表头的普通矩阵
+        let file = dir.join(format!("matrix_{}.txt", std::process::id()));
+        std::fs::write(&file, "A 1 2\nB 3 4\n").unwrap();
+        let m = read_expression_matrix(file.to_str().unwrap(), MissingValue::Fill(0.));
+        assert_eq!(m.columns, vec!["t0", "t1"]);
+        assert_eq!(m.profiles["B"], vec![3., 4.]);
+        std::fs::remove_file(file).unwrap();
+    }
 
     #[test]
     fn test_build_dpins() {
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     fs::read_to_string,
     process::id,
 };
@@ -16,7 +16,7 @@ pub const DEFAULT_EDGE_WEIGHT: f64 = 1.;
 pub struct Graph {
     pub(crate) node_count: usize,
     pub(crate) edge_count: usize,
-    pub(crate) nei_list: Vec<HashMap<usize, f64>>,
+    pub(crate) nei_list: Vec<BTreeMap<usize, f64>>,
     pub(crate) node_weight: Vec<f64>,
     pub(crate) id_protein: Vec<String>,
 }
@@ -42,7 +42,7 @@ impl Graph {
             + 1;
 
         let mut edge_count = 0;
-        let mut nei_list = vec![<HashMap<usize, f64>>::new(); node_count];
+        let mut nei_list = vec![<BTreeMap<usize, f64>>::new(); node_count];
         edges.clone().into_iter().for_each(|(a, b, w)| {
             nei_list[a].insert(b, w);
             nei_list[b].insert(a, w);
@@ -83,25 +83,32 @@ impl Graph {
     }
 
     // 从节点解析出连边数据
+    // 子图中的节点按照原图中的id顺序编号，保证结果可复现
     pub fn subgraph(&self, nodes: &HashSet<String>) -> Self {
-        let id_protein: Vec<String> = nodes.clone().into_iter().collect();
+        let id_protein: Vec<String> = self
+            .id_protein
+            .iter()
+            .filter(|p| nodes.contains(*p))
+            .cloned()
+            .collect();
         let protein_id = id_protein
             .iter()
             .enumerate()
             .map(|(id, protein)| (protein.to_string(), id))
             .collect::<HashMap<String, usize>>();
-        let mut edges_id = Vec::<(usize, usize, f64)>::new();
+        // 孤立节点和空子图同样保留
+        let mut graph = Self::new(id_protein.len());
+        graph.node_weight = vec![DEFAULT_NODE_WEIGHT; id_protein.len()];
         self.nei_list.iter().enumerate().for_each(|(a, nei)| {
             nei.iter().for_each(|(b, w)| {
                 let a = protein_id.get(&self.id_protein[a]);
                 let b = protein_id.get(&self.id_protein[*b]);
                 if let (Some(a), Some(b)) = (a, b) {
-                    edges_id.push((*a, *b, *w));
+                    graph.nei_list[*a].insert(*b, *w);
+                    graph.edge_count += 1;
                 }
             });
         });
-
-        let mut graph: Self = Self::new_from(edges_id);
         graph.id_protein = id_protein;
         graph.edge_count /= 2;
 
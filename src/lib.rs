@@ -4,13 +4,24 @@ pub mod eps;
 #[allow(unused)]
 mod matrix;
 
+pub mod catalog;
 #[allow(unused)]
 mod cen;
+pub mod coherence;
+pub mod consolidate;
 pub mod dag;
+pub mod dynamic;
+pub mod enrichment;
 #[allow(unused)]
 pub mod eva;
 pub mod gene_expression;
 pub mod go_finder;
 #[allow(unused)]
 pub mod graph;
+pub mod matching;
 pub mod pcegs;
+pub mod refine;
+pub mod significance;
+pub mod stats;
+pub mod tracking;
+pub mod writer;
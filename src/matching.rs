@@ -0,0 +1,150 @@
+/// 二分图最大权匹配（匈牙利算法），用于计算 MMR
+use crate::complex::union::UnionFind;
+
+// weights[i][j] 为左侧节点i与右侧节点j之间的权重，不大于0表示没有连边
+// 返回匹配的 (i, j, weight)，按 (i, j) 排序
+pub fn max_weight_matching(weights: &[Vec<f64>]) -> Vec<(usize, usize, f64)> {
+    let n = weights.len();
+    let m = weights.first().map_or(0, |r| r.len());
+
+    // 按连通分量分别求解，参考复合物与预测复合物之间的连边通常很稀疏
+    let mut edges = Vec::new();
+    for (i, row) in weights.iter().enumerate() {
+        for (j, w) in row.iter().enumerate() {
+            if *w > 0. {
+                edges.push((i, n + j));
+            }
+        }
+    }
+    let mut union_find = UnionFind::new_from(n + m, edges);
+
+    let mut pairs = Vec::new();
+    for component in union_find.get_components() {
+        let rows = component
+            .iter()
+            .filter(|v| **v < n)
+            .copied()
+            .collect::<Vec<_>>();
+        let cols = component
+            .iter()
+            .filter(|v| **v >= n)
+            .map(|v| v - n)
+            .collect::<Vec<_>>();
+        if rows.is_empty() || cols.is_empty() {
+            continue;
+        }
+
+        // 匈牙利算法要求行数不多于列数
+        let assignment = if rows.len() <= cols.len() {
+            let sub = rows
+                .iter()
+                .map(|i| cols.iter().map(|j| weights[*i][*j]).collect())
+                .collect::<Vec<_>>();
+            hungarian(&sub)
+                .into_iter()
+                .map(|(a, b)| (rows[a], cols[b]))
+                .collect::<Vec<_>>()
+        } else {
+            let sub = cols
+                .iter()
+                .map(|j| rows.iter().map(|i| weights[*i][*j]).collect())
+                .collect::<Vec<_>>();
+            hungarian(&sub)
+                .into_iter()
+                .map(|(b, a)| (rows[a], cols[b]))
+                .collect::<Vec<_>>()
+        };
+        pairs.extend(
+            assignment
+                .into_iter()
+                .filter(|(i, j)| weights[*i][*j] > 0.)
+                .map(|(i, j)| (i, j, weights[i][j])),
+        );
+    }
+
+    pairs.sort_by_key(|(i, j, _)| (*i, *j));
+    pairs
+}
+
+// 行数 n 不多于列数 m 时，为每一行分配不同的列使权重之和最大，复杂度 O(n^2 m)
+fn hungarian(weights: &[Vec<f64>]) -> Vec<(usize, usize)> {
+    let n = weights.len();
+    let m = weights[0].len();
+    // 下标从1开始，p[j] 为分配到第j列的行，0 表示未分配
+    let (mut u, mut v) = (vec![0.; n + 1], vec![0.; m + 1]);
+    let mut p = vec![0; m + 1];
+    let mut way = vec![0; m + 1];
+
+    for i in 1..=n {
+        p[0] = i;
+        let mut j0 = 0;
+        let mut minv = vec![f64::INFINITY; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                // 最大化权重即最小化其相反数
+                let cur = -weights[i0 - 1][j - 1] - u[i0] - v[j];
+                if cur < minv[j] {
+                    minv[j] = cur;
+                    way[j] = j0;
+                }
+                if minv[j] < delta {
+                    delta = minv[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    (1..=m)
+        .filter(|j| p[*j] != 0)
+        .map(|j| (p[j] - 1, j - 1))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::max_weight_matching;
+
+    #[test]
+    fn test_max_weight_matching() {
+        // 贪心会选择 (0, 0)，最优匹配为 (0, 1) 和 (1, 0)
+        let weights = vec![vec![0.9, 0.8, 0.], vec![0.7, 0., 0.], vec![0., 0., 0.]];
+        let pairs = max_weight_matching(&weights);
+        assert_eq!(pairs, vec![(0, 1, 0.8), (1, 0, 0.7)]);
+
+        // 行数多于列数，以及相互独立的连通分量
+        let weights = vec![vec![0.5, 0.], vec![0.6, 0.], vec![0., 0.3]];
+        let pairs = max_weight_matching(&weights);
+        assert_eq!(pairs, vec![(1, 0, 0.6), (2, 1, 0.3)]);
+
+        assert!(max_weight_matching(&[]).is_empty());
+    }
+}
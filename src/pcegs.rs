@@ -1,11 +1,23 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    io::Write,
+};
+
+use rayon::prelude::*;
+use serde::Serialize;
 
 use crate::{
-    eva::{update_by_cohesion, Complex},
-    gene_expression::read_essential_protein,
+    dag::{weight_by_dag_topo_with, Dag},
+    eva::{select_by_cohesion, update_by_cohesion, Complex, SelectOptions, Selection},
+    gene_expression::{get_dpins, read_essential_protein},
     graph::Graph,
+    refine::RefineOptions,
+    writer::create_file,
 };
 
+// 并行模式下每个线程每批扩展的种子数
+const PARALLEL_BATCH: usize = 4;
+
 impl Graph {
     pub fn all_cc(&self) -> Vec<f64> {
         (0..self.node_count)
@@ -41,16 +53,238 @@ impl Graph {
         actual_edges as f64 / (max_possible_edges as f64 * 2.0)
     }
 
+    // 按聚类系数从大到小排序，聚类系数相同时id小的优先
     pub fn cc_sort(&self) -> Vec<usize> {
-        let cc = self.all_cc();
+        self.seed_sort(SeedScore::Clustering)
+    }
+
+    // 按种子得分从大到小排序，得分相同时id小的优先
+    pub fn seed_sort(&self, score: SeedScore) -> Vec<usize> {
+        let scores = self.seed_scores(score);
         let mut seed = (0..self.node_count).collect::<Vec<_>>();
-        seed.sort_by(|a, b| cc[*a].partial_cmp(&cc[*b]).unwrap());
-        seed.reverse();
+        seed.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]).then(a.cmp(b)));
         seed
     }
+
+    pub fn seed_scores(&self, score: SeedScore) -> Vec<f64> {
+        // 加权聚类系数中的权重按最大边权归一化
+        let max_weight = self
+            .nei_list
+            .iter()
+            .flat_map(|nei| nei.values())
+            .fold(0., |m: f64, w| m.max(*w));
+        (0..self.node_count)
+            .map(|node| match score {
+                SeedScore::Clustering => self.clustering_coefficient(node),
+                SeedScore::Onnela => self.onnela_cc(node, max_weight),
+                SeedScore::Barrat => self.barrat_cc(node),
+                SeedScore::ZhangHorvath => self.zhang_horvath_cc(node, max_weight),
+                SeedScore::LocalDensity => self.local_density(node),
+            })
+            .collect()
+    }
+
+    // 节点的邻居两两之间存在的边: (w_ij, w_ih, w_jh)
+    fn neighbor_triangles(&self, node: usize) -> Vec<(f64, f64, f64)> {
+        let neighbors = self.nei_list[node].iter().collect::<Vec<_>>();
+        let mut triangles = Vec::new();
+        for (p, (j, w_ij)) in neighbors.iter().enumerate() {
+            for (h, w_ih) in neighbors[p + 1..].iter() {
+                if let Some(w_jh) = self.nei_list[**j].get(h) {
+                    triangles.push((**w_ij, **w_ih, *w_jh));
+                }
+            }
+        }
+        triangles
+    }
+
+    // Onnela et al. 2005: 三角形边权几何平均数之和
+    pub fn onnela_cc(&self, node: usize, max_weight: f64) -> f64 {
+        let k = self.nei_list[node].len() as f64;
+        if k < 2. || max_weight <= 0. {
+            return 0.;
+        }
+        let sum = self
+            .neighbor_triangles(node)
+            .into_iter()
+            .map(|(a, b, c)| (a * b * c / max_weight.powi(3)).cbrt())
+            .sum::<f64>();
+        2. * sum / (k * (k - 1.))
+    }
+
+    // Barrat et al. 2004: 以节点强度归一化的闭合三元组边权
+    pub fn barrat_cc(&self, node: usize) -> f64 {
+        let k = self.nei_list[node].len() as f64;
+        let strength = self.nei_list[node].values().sum::<f64>();
+        if k < 2. || strength <= 0. {
+            return 0.;
+        }
+        let sum = self
+            .neighbor_triangles(node)
+            .into_iter()
+            .map(|(w_ij, w_ih, _)| w_ij + w_ih)
+            .sum::<f64>();
+        sum / (strength * (k - 1.))
+    }
+
+    // Zhang & Horvath 2005: 加权共表达网络中的聚类系数
+    pub fn zhang_horvath_cc(&self, node: usize, max_weight: f64) -> f64 {
+        if self.nei_list[node].len() < 2 || max_weight <= 0. {
+            return 0.;
+        }
+        let weights = self.nei_list[node]
+            .values()
+            .map(|w| w / max_weight)
+            .collect::<Vec<_>>();
+        let strength = weights.iter().sum::<f64>();
+        let denom = strength.powi(2) - weights.iter().map(|w| w * w).sum::<f64>();
+        if denom <= 0. {
+            return 0.;
+        }
+        let sum = self
+            .neighbor_triangles(node)
+            .into_iter()
+            .map(|(a, b, c)| a * b * c / max_weight.powi(3))
+            .sum::<f64>();
+        2. * sum / denom
+    }
+
+    // 节点及其邻居构成的子图的加权密度
+    pub fn local_density(&self, node: usize) -> f64 {
+        let k = self.nei_list[node].len() as f64;
+        if k < 1. {
+            return 0.;
+        }
+        let inner = self
+            .neighbor_triangles(node)
+            .into_iter()
+            .map(|(_, _, w_jh)| w_jh)
+            .sum::<f64>();
+        let total = self.nei_list[node].values().sum::<f64>() + inner;
+        // 闭邻域中共有 k + 1 个节点
+        total / (k * (k + 1.) / 2.)
+    }
+}
+
+// 种子节点的排序依据
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedScore {
+    // 不考虑边权的聚类系数
+    #[default]
+    Clustering,
+    Onnela,
+    Barrat,
+    ZhangHorvath,
+    LocalDensity,
+}
+
+// 附属节点的候选记录
+#[derive(Debug, Clone, Serialize)]
+pub struct Attachment {
+    pub protein: String,
+    // 吸引力比值 sum_a / node_influ
+    pub ratio: f64,
+    // ratio >= bate 时被接受为附属
+    pub accepted: bool,
+}
+
+// 候选复合物的最终去向
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Fate {
+    Kept,
+    // 与内聚力更高的复合物重叠，记录该复合物的种子
+    Overlapped { suppressed_by: String },
+    // 合并到其他复合物中，记录合并后复合物的种子
+    Merged { into: String },
+    // 未进入内聚力排名的保留比例
+    Truncated,
+    // 成员数少于3
+    TooSmall,
+}
+
+// 复合物的来源记录，解释复合物是如何产生的
+#[derive(Debug, Clone, Serialize)]
+pub struct Provenance {
+    // 所在动态子网络的时间点，静态网络为None
+    pub time_point: Option<usize>,
+    pub seed: String,
+    // 种子排序所用的得分及其类型
+    pub seed_score: f64,
+    pub seed_score_kind: SeedScore,
+    pub core: Vec<String>,
+    pub attachments: Vec<Attachment>,
+    pub bate: f64,
+    pub proteins: Vec<String>,
+    // 迭代优化中加入和删除的蛋白质
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub refine_added: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub refine_removed: Vec<String>,
+    pub cohesion: f64,
+    pub fate: Fate,
+}
+
+#[derive(Debug, Clone)]
+pub struct PcegsOptions {
+    // 功能相似性与拓扑相似性的权衡系数
+    pub alpha: f64,
+    // 附属节点的吸引力阈值
+    pub bate: f64,
+    // 重叠复合物的处理与内聚力截断
+    pub selection: SelectOptions,
+    // 种子节点的排序依据
+    pub seed_score: SeedScore,
+    // 识别后对候选复合物进行迭代优化
+    pub refine: Option<RefineOptions>,
+    // 并行扩展种子节点：按排名每次取一批尚未被覆盖的种子并行扩展，
+    // 同一批中被排名更高的种子覆盖的种子会多做一次扩展，结果与串行一致
+    pub parallel: bool,
+}
+
+impl Default for PcegsOptions {
+    fn default() -> Self {
+        Self {
+            alpha: 0.5,
+            bate: 0.4,
+            selection: SelectOptions::default(),
+            seed_score: SeedScore::default(),
+            refine: None,
+            parallel: false,
+        }
+    }
+}
+
+impl PcegsOptions {
+    pub fn new(alpha: f64, bate: f64) -> Self {
+        Self {
+            alpha,
+            bate,
+            ..Default::default()
+        }
+    }
 }
 
 pub fn pcegs(graph: &Graph, bate: f64) -> Vec<Complex<String>> {
+    let options = PcegsOptions {
+        bate,
+        ..Default::default()
+    };
+    pcegs_explained(graph, &options).0
+}
+
+// 识别复合物，同时返回每个候选复合物的来源记录
+pub fn pcegs_explained(
+    graph: &Graph,
+    options: &PcegsOptions,
+) -> (Vec<Complex<String>>, Vec<Provenance>) {
+    let bate = options.bate;
+    let names = |ids: &[usize]| {
+        ids.iter()
+            .map(|id| graph.id_protein[*id].to_string())
+            .collect::<Vec<_>>()
+    };
     // 计算节点的权重
     let node_weight = graph
         .nei_list
@@ -68,93 +302,241 @@ pub fn pcegs(graph: &Graph, bate: f64) -> Vec<Complex<String>> {
     });
 
     // 节点的影响力
-    let mut node_influ = HashMap::new();
-    graph.nei_list.iter().enumerate().for_each(|(n, nei)| {
-        let att = nei
-            .iter()
-            .map(|(m, _)| a.get(&(*m, n)).unwrap())
-            .sum::<f64>();
-        node_influ.insert(n, att);
-    });
+    let node_influ = graph
+        .nei_list
+        .iter()
+        .enumerate()
+        .map(|(n, nei)| nei.keys().map(|m| a[&(*m, n)]).sum::<f64>())
+        .collect::<Vec<_>>();
 
     // step 1. 获取种子节点
-    let seeds = graph.cc_sort();
+    let seed_scores = graph.seed_scores(options.seed_score);
+    let seeds = graph.seed_sort(options.seed_score);
     let mut visited = HashSet::<usize>::new();
 
+    let expand = |seed: usize| expand_seed(graph, seed, &a, &node_influ, options);
+    // 并行模式下预先扩展的种子，键为种子在排名中的下标
+    let mut expansions = HashMap::<usize, Expansion>::new();
+    let batch = rayon::current_num_threads() * PARALLEL_BATCH;
+
     let mut complexes = Vec::new();
-    for seed in seeds {
+    let mut records = Vec::new();
+    // 候选复合物对应的来源记录下标
+    let mut candidate_record = Vec::new();
+    for (i, seed) in seeds.iter().copied().enumerate() {
         if visited.contains(&seed) {
             continue;
         }
-        // 核心
-        let mut core = graph.nei_list[seed]
-            .keys()
-            .map(|c| c.to_owned())
-            .collect::<HashSet<usize>>();
-        // 候选附属,即核心对应的邻居
-        let mut all_neis = HashSet::<usize>::new();
-        core.iter().for_each(|c| {
-            if let Some(ns) = graph.nei_list.get(*c) {
-                ns.keys().for_each(|m| {
-                    if !core.contains(m) {
-                        all_neis.insert(*m);
-                    }
-                });
-            }
-        });
-        core.insert(seed);
-        // nei.insert(seed);
-        // let core = nei;
+        if options.parallel && !expansions.contains_key(&i) {
+            // 扩展不依赖访问标记，从当前种子开始取一批尚未被覆盖的种子并行扩展
+            let next = seeds[i..]
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| !visited.contains(*s))
+                .take(batch)
+                .map(|(k, s)| (i + k, *s))
+                .collect::<Vec<_>>();
+            expansions.extend(
+                next.into_par_iter()
+                    .map(|(k, s)| (k, expand(s)))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        let expansion = expansions.remove(&i).unwrap_or_else(|| expand(seed));
         // 更新访问过的节点
-        core.iter().for_each(|n| {
+        expansion.core.iter().for_each(|n| {
             visited.insert(*n);
         });
-        // 根据情况是否将其加入到
-        let mut real_attach = HashSet::<usize>::new();
-        // 计算社区对节点的吸引力
-        for n in all_neis {
-            let sum_a = core
+        let mut record = Provenance {
+            time_point: None,
+            seed: graph.id_protein[seed].to_string(),
+            seed_score: seed_scores[seed],
+            seed_score_kind: options.seed_score,
+            core: names(&expansion.core.iter().copied().collect::<Vec<_>>()),
+            attachments: expansion
+                .attachments
                 .iter()
-                .map(|d| match a.get(&(*d, n)) {
-                    Some(v) => *v,
-                    None => 0.,
+                .map(|(n, ratio)| Attachment {
+                    protein: graph.id_protein[*n].to_string(),
+                    ratio: *ratio,
+                    accepted: ratio.ge(&bate),
                 })
-                .sum::<f64>();
+                .collect(),
+            bate,
+            proteins: names(&expansion.proteins),
+            refine_added: names(&expansion.added),
+            refine_removed: names(&expansion.removed),
+            cohesion: 0.,
+            fate: Fate::TooSmall,
+        };
+        if let Some(cohesion) = expansion.cohesion {
+            record.cohesion = cohesion;
+            candidate_record.push(records.len());
+            complexes.push(Complex {
+                proteins: expansion.proteins,
+                cohesion,
+            });
+        }
+        records.push(record);
+    }
+
+    let (kept, selection) = select_by_cohesion(&complexes, &options.selection, |proteins| {
+        graph.calculate_cohesion(&proteins.to_vec())
+    });
+    selection.into_iter().enumerate().for_each(|(i, s)| {
+        let seed = |j: usize| records[candidate_record[j]].seed.clone();
+        records[candidate_record[i]].fate = match s {
+            Selection::Kept => Fate::Kept,
+            Selection::Overlapped(j) => Fate::Overlapped {
+                suppressed_by: seed(j),
+            },
+            Selection::Merged(j) => Fate::Merged { into: seed(j) },
+            Selection::Truncated => Fate::Truncated,
+        };
+    });
+
+    let result = kept
+        .into_iter()
+        .map(|c| Complex {
+            proteins: names(&c.proteins),
+            cohesion: c.cohesion,
+        })
+        .collect::<Vec<_>>();
+    (result, records)
+}
 
-            match (sum_a / node_influ[&n]).ge(&bate) {
-                true => {
-                    real_attach.insert(n);
+// 单个种子节点扩展得到的候选复合物
+struct Expansion {
+    core: BTreeSet<usize>,
+    // (候选附属节点, 吸引力比值)
+    attachments: Vec<(usize, f64)>,
+    proteins: Vec<usize>,
+    // 迭代优化中加入和删除的节点
+    added: Vec<usize>,
+    removed: Vec<usize>,
+    // 成员数少于3时为None
+    cohesion: Option<f64>,
+}
+
+// 以种子节点及其邻居为核心，根据吸引力比值添加附属节点
+fn expand_seed(
+    graph: &Graph,
+    seed: usize,
+    a: &HashMap<(usize, usize), f64>,
+    node_influ: &[f64],
+    options: &PcegsOptions,
+) -> Expansion {
+    let bate = options.bate;
+    // 核心
+    let mut core = graph.nei_list[seed]
+        .keys()
+        .map(|c| c.to_owned())
+        .collect::<BTreeSet<usize>>();
+    // 候选附属,即核心对应的邻居
+    let mut all_neis = BTreeSet::<usize>::new();
+    core.iter().for_each(|c| {
+        if let Some(ns) = graph.nei_list.get(*c) {
+            ns.keys().for_each(|m| {
+                if !core.contains(m) {
+                    all_neis.insert(*m);
                 }
-                false => {}
-            };
+            });
         }
-        let proteins = core
-            .union(&real_attach)
-            .map(|a| a.clone())
-            .collect::<HashSet<_>>()
-            .into_iter()
-            .collect::<Vec<_>>();
-        if proteins.len() >= 3 {
-            let cohesion = graph.calculate_cohesion(&proteins);
-            let complex = Complex { proteins, cohesion };
-            complexes.push(complex);
+    });
+    core.insert(seed);
+    // 根据情况是否将其加入到
+    let mut real_attach = BTreeSet::<usize>::new();
+    let mut attachments = Vec::new();
+    // 计算社区对节点的吸引力
+    for n in all_neis {
+        let sum_a = core
+            .iter()
+            .map(|d| match a.get(&(*d, n)) {
+                Some(v) => *v,
+                None => 0.,
+            })
+            .sum::<f64>();
+
+        let ratio = sum_a / node_influ[n];
+        if ratio.ge(&bate) {
+            real_attach.insert(n);
         }
+        attachments.push((n, ratio));
     }
-    let complexes_id = update_by_cohesion(complexes);
-    complexes_id
+    let mut proteins = core.union(&real_attach).copied().collect::<Vec<_>>();
+    let (mut added, mut removed) = (Vec::new(), Vec::new());
+    if let (Some(refine), true) = (&options.refine, proteins.len() >= 3) {
+        let refined = graph.refine_complex(&proteins, refine);
+        added = refined
+            .iter()
+            .filter(|p| !proteins.contains(p))
+            .copied()
+            .collect();
+        removed = proteins
+            .iter()
+            .filter(|p| !refined.contains(p))
+            .copied()
+            .collect();
+        proteins = refined;
+    }
+    let cohesion = if proteins.len() >= 3 {
+        Some(graph.calculate_cohesion(&proteins))
+    } else {
+        None
+    };
+
+    Expansion {
+        core,
+        attachments,
+        proteins,
+        added,
+        removed,
+        cohesion,
+    }
+}
+
+// 在每个动态子网络上识别复合物，并按时间点顺序合并结果
+pub fn pcegs_dpins(graph: &Graph, alpha: f64, bate: f64) -> Vec<Complex<String>> {
+    pcegs_dpins_explained(graph, &PcegsOptions::new(alpha, bate))
+        .0
         .into_iter()
-        .map(|c| {
-            let proteins = c
-                .proteins
-                .into_iter()
-                .map(|id| graph.id_protein[id].to_string())
-                .collect::<Vec<_>>();
-            Complex {
-                proteins,
-                cohesion: c.cohesion,
-            }
-        })
-        .collect::<Vec<_>>()
+        .flatten()
+        .collect()
+}
+
+// 返回每个时间点识别出的复合物以及所有候选复合物的来源记录
+pub fn pcegs_dpins_explained(
+    graph: &Graph,
+    options: &PcegsOptions,
+) -> (Vec<Vec<Complex<String>>>, Vec<Provenance>) {
+    pcegs_dpins_with(get_dpins(graph), options)
+}
+
+// 在给定的动态子网络（如 DpinBuilder 构建的）上识别复合物
+pub fn pcegs_dpins_with(
+    dpins: Vec<Graph>,
+    options: &PcegsOptions,
+) -> (Vec<Vec<Complex<String>>>, Vec<Provenance>) {
+    let mut dag = Dag::new();
+    let mut complexes = Vec::new();
+    let mut records = Vec::new();
+    for (t, mut dp) in dpins.into_iter().enumerate() {
+        weight_by_dag_topo_with(&mut dp, &mut dag, options.alpha);
+        let (res, mut rec) = pcegs_explained(&dp, options);
+        rec.iter_mut().for_each(|r| r.time_point = Some(t));
+        complexes.push(res);
+        records.extend(rec);
+    }
+    (complexes, records)
+}
+
+// 将来源记录按JSON lines格式写入文件
+pub fn write_provenance(file: &str, records: &[Provenance]) {
+    let mut file = create_file(file).expect("Failed to create provenance file!");
+    for record in records {
+        let line = serde_json::to_string(record).expect("Failed to serialize provenance!");
+        writeln!(file, "{}", line).expect("Failed to write to file!");
+    }
 }
 
 pub fn pcegs_essential(graph: &Graph, bate: f64) -> Vec<Complex<String>> {
@@ -175,14 +557,12 @@ pub fn pcegs_essential(graph: &Graph, bate: f64) -> Vec<Complex<String>> {
     });
 
     // 节点的影响力
-    let mut node_influ = HashMap::new();
-    graph.nei_list.iter().enumerate().for_each(|(n, nei)| {
-        let att = nei
-            .iter()
-            .map(|(m, _)| a.get(&(*m, n)).unwrap())
-            .sum::<f64>();
-        node_influ.insert(n, att);
-    });
+    let node_influ = graph
+        .nei_list
+        .iter()
+        .enumerate()
+        .map(|(n, nei)| nei.keys().map(|m| a[&(*m, n)]).sum::<f64>())
+        .collect::<Vec<_>>();
 
     // step 1. 获取种子节点
     let seeds = graph.cc_sort();
@@ -202,14 +582,14 @@ pub fn pcegs_essential(graph: &Graph, bate: f64) -> Vec<Complex<String>> {
         // 核心
         let mut core = graph.nei_list[seed]
             .keys()
-            .map(|a| a.clone())
+            .copied()
             .collect::<HashSet<_>>()
             .intersection(&eps)
-            .map(|a| a.clone())
-            .collect::<HashSet<_>>();
+            .copied()
+            .collect::<BTreeSet<_>>();
 
         // 候选附属,即核心对应的邻居
-        let mut all_neis = HashSet::<usize>::new();
+        let mut all_neis = BTreeSet::<usize>::new();
         core.iter().for_each(|c| {
             if let Some(ns) = graph.nei_list.get(*c) {
                 ns.keys().for_each(|m| {
@@ -227,7 +607,7 @@ pub fn pcegs_essential(graph: &Graph, bate: f64) -> Vec<Complex<String>> {
             visited.insert(*n);
         });
         // 根据情况是否将其加入到
-        let mut real_attach = HashSet::<usize>::new();
+        let mut real_attach = BTreeSet::<usize>::new();
         // 计算社区对节点的吸引力
         for n in all_neis {
             let sum_a = core
@@ -238,19 +618,11 @@ pub fn pcegs_essential(graph: &Graph, bate: f64) -> Vec<Complex<String>> {
                 })
                 .sum::<f64>();
 
-            match (sum_a / node_influ[&n]).ge(&bate) {
-                true => {
-                    real_attach.insert(n);
-                }
-                false => {}
-            };
+            if sum_a / node_influ[n] >= bate {
+                real_attach.insert(n);
+            }
         }
-        let proteins = core
-            .union(&real_attach)
-            .map(|a| a.clone())
-            .collect::<HashSet<_>>()
-            .into_iter()
-            .collect::<Vec<_>>();
+        let proteins = core.union(&real_attach).copied().collect::<Vec<_>>();
         if proteins.len() >= 3 {
             let cohesion = graph.calculate_cohesion(&proteins);
             let complex = Complex { proteins, cohesion };
@@ -276,9 +648,9 @@ pub fn pcegs_essential(graph: &Graph, bate: f64) -> Vec<Complex<String>> {
 
 #[cfg(test)]
 mod tests {
-    use crate::graph::Graph;
+    use crate::{eva::Complex, graph::Graph, refine::RefineOptions};
 
-    use super::pcegs;
+    use super::{pcegs, pcegs_dpins, pcegs_explained, Fate, PcegsOptions, SeedScore};
 
     #[test]
     fn test_cc() {
@@ -302,9 +674,129 @@ mod tests {
         println!("{:?}", seed)
     }
 
+    #[test]
+    fn test_weighted_cc() {
+        let g = Graph::new_from(vec![
+            (0, 1, 1.),
+            (0, 2, 1.),
+            (0, 3, 0.5),
+            (1, 2, 1.),
+            (2, 3, 0.5),
+        ]);
+        // 所有边权相同时加权聚类系数退化为聚类系数
+        let unit = Graph::new_from(vec![(0, 1, 1.), (0, 2, 1.), (0, 3, 1.), (1, 2, 1.)]);
+        for score in [
+            SeedScore::Onnela,
+            SeedScore::Barrat,
+            SeedScore::ZhangHorvath,
+        ] {
+            let weighted = unit.seed_scores(score);
+            let cc = unit.all_cc();
+            weighted
+                .iter()
+                .zip(cc.iter())
+                .for_each(|(w, c)| assert!((w - c).abs() < 1e-12));
+        }
+
+        let cc = g.all_cc();
+        let onnela = g.seed_scores(SeedScore::Onnela);
+        let barrat = g.seed_scores(SeedScore::Barrat);
+        let zh = g.seed_scores(SeedScore::ZhangHorvath);
+        // 节点2的两个三角形中一个包含弱边
+        assert!(onnela[2] < cc[2]);
+        assert!(barrat[2] > 0. && zh[2] < cc[2]);
+        assert!((g.local_density(1) - 1.).abs() < 1e-12);
+
+        let seeds = g.seed_sort(SeedScore::LocalDensity);
+        assert_eq!(seeds[0], 1);
+    }
+
     #[test]
     fn test_pcegs() {
         let graph = Graph::new_from_file("./data/collins/collins.txt", true);
         pcegs(&graph, 0.4);
     }
+
+    #[test]
+    fn test_pcegs_explained() {
+        let graph = Graph::new_from_file("./data/collins/collins.txt", true);
+        let (complexes, records) = pcegs_explained(&graph, &PcegsOptions::default());
+        let kept = records
+            .iter()
+            .filter(|r| r.fate == Fate::Kept)
+            .collect::<Vec<_>>();
+        assert_eq!(kept.len(), complexes.len());
+        for r in records.iter() {
+            assert!(r.proteins.contains(&r.seed));
+            if let Fate::Overlapped { suppressed_by } = &r.fate {
+                assert_ne!(suppressed_by, &r.seed);
+            }
+        }
+
+        // 序列化后记录种子得分及其类型
+        let json = serde_json::to_value(&records[0]).unwrap();
+        assert_eq!(json["seed"], records[0].seed.as_str());
+        assert_eq!(json["seed_score"], records[0].seed_score);
+        assert_eq!(json["seed_score_kind"], "clustering");
+        assert_eq!(
+            json["proteins"].as_array().unwrap().len(),
+            records[0].proteins.len()
+        );
+    }
+
+    #[test]
+    fn test_pcegs_parallel() {
+        let graph = Graph::new_from_file("./data/collins/collins.txt", true);
+        let output = |complexes: &[Complex<String>]| {
+            complexes
+                .iter()
+                .map(|c| format!("{}\n", c))
+                .collect::<String>()
+        };
+        // 分批并行扩展（包括迭代优化）与串行结果一致
+        for refine in [None, Some(RefineOptions::default())] {
+            let mut options = PcegsOptions {
+                refine,
+                ..Default::default()
+            };
+            let (sequential, records) = pcegs_explained(&graph, &options);
+            options.parallel = true;
+            let (parallel, parallel_records) = pcegs_explained(&graph, &options);
+
+            assert_eq!(output(&sequential), output(&parallel));
+            assert_eq!(
+                serde_json::to_string(&records).unwrap(),
+                serde_json::to_string(&parallel_records).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_pcegs_refine() {
+        let graph = Graph::new_from_file("./data/collins/collins.txt", true);
+        let options = PcegsOptions {
+            refine: Some(RefineOptions::default()),
+            ..Default::default()
+        };
+        let (complexes, records) = pcegs_explained(&graph, &options);
+        assert!(!complexes.is_empty());
+        for r in records.iter().filter(|r| r.fate == Fate::Kept) {
+            assert!(r.refine_added.iter().all(|p| r.proteins.contains(p)));
+            assert!(r.refine_removed.iter().all(|p| !r.proteins.contains(p)));
+        }
+    }
+
+    #[test]
+    fn test_pcegs_dpins_reproducible() {
+        let run = || {
+            let graph = Graph::new_from_file("./data/test/test.txt", true);
+            pcegs_dpins(&graph, 0.5, 0.4)
+                .iter()
+                .map(|c| format!("{}\n", c))
+                .collect::<String>()
+        };
+        let first = run();
+        assert!(!first.is_empty());
+        assert_eq!(first, run());
+    }
 }
@@ -0,0 +1,310 @@
+/// 复合物的迭代优化：贪心地加入边界蛋白质或删除成员，直到适应度不再提高
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::{
+    eva::{MAX_SIZE, MIN_SIZE},
+    graph::Graph,
+};
+
+/// ClusterONE 默认的惩罚项
+pub const DEFAULT_PENALTY: f64 = 2.;
+pub const MAX_ITER: usize = 100;
+
+// 优化的目标函数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fitness {
+    // Graph::calculate_cohesion
+    Cohesion,
+    // ClusterONE 的 cohesiveness，参数为惩罚项
+    Cohesiveness(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RefineOptions {
+    pub fitness: Fitness,
+    pub min_size: usize,
+    pub max_size: usize,
+    pub max_iter: usize,
+}
+
+impl Default for RefineOptions {
+    fn default() -> Self {
+        Self {
+            fitness: Fitness::Cohesion,
+            min_size: MIN_SIZE,
+            max_size: MAX_SIZE,
+            max_iter: MAX_ITER,
+        }
+    }
+}
+
+impl Graph {
+    // ClusterONE: w_in / (w_in + w_bound + penalty * |V|)
+    pub fn cohesiveness(&self, cluster: &[usize], penalty: f64) -> f64 {
+        let members = cluster.iter().collect::<HashSet<_>>();
+        let (mut w_in, mut w_bound) = (0., 0.);
+        for i in cluster.iter() {
+            for (j, w) in self.nei_list[*i].iter() {
+                if members.contains(j) {
+                    w_in += w;
+                } else {
+                    w_bound += w;
+                }
+            }
+        }
+        // 内部边被计算了两次
+        w_in /= 2.;
+
+        let denom = w_in + w_bound + penalty * cluster.len() as f64;
+        if denom <= 0. {
+            0.
+        } else {
+            w_in / denom
+        }
+    }
+
+    pub fn fitness(&self, cluster: &[usize], fitness: Fitness) -> f64 {
+        match fitness {
+            Fitness::Cohesion => self.calculate_cohesion(&cluster.to_vec()),
+            Fitness::Cohesiveness(penalty) => self.cohesiveness(cluster, penalty),
+        }
+    }
+
+    // 每轮选择使适应度提高最多的一步（加入一个边界节点或删除一个成员），
+    // 没有能提高适应度的操作时停止；得分相同时先加入后删除、id小的优先
+    pub fn refine_complex(&self, proteins: &[usize], options: &RefineOptions) -> Vec<usize> {
+        let mut state = ClusterState::new(self, proteins);
+        let mut score = state.fitness(options.fitness);
+
+        for _ in 0..options.max_iter {
+            let mut moves = Vec::<(usize, bool)>::new();
+            if state.members.len() < options.max_size {
+                let boundary = state
+                    .members
+                    .iter()
+                    .flat_map(|i| self.nei_list[*i].keys())
+                    .filter(|j| !state.members.contains(j))
+                    .copied()
+                    .collect::<BTreeSet<_>>();
+                moves.extend(boundary.into_iter().map(|b| (b, true)));
+            }
+            if state.members.len() > options.min_size {
+                moves.extend(state.members.iter().map(|m| (*m, false)));
+            }
+
+            let mut best: Option<(f64, (usize, bool))> = None;
+            for (node, add) in moves {
+                let s = state.moved(node, add).fitness(options.fitness);
+                if best.is_none_or(|(b, _)| s > b) {
+                    best = Some((s, (node, add)));
+                }
+            }
+
+            match best {
+                Some((s, (node, add))) if s > score + f64::EPSILON => {
+                    score = s;
+                    state.apply(node, add);
+                }
+                _ => break,
+            }
+        }
+
+        state.members.into_iter().collect()
+    }
+}
+
+// 迭代优化过程中维护的簇状态，用于增量计算适应度
+struct ClusterState<'a> {
+    graph: &'a Graph,
+    members: BTreeSet<usize>,
+    // 成员在簇内的邻居数及边权之和
+    count: HashMap<usize, f64>,
+    weight: HashMap<usize, f64>,
+    // sum(weight_i * (count_i + 1))，即 calculate_cohesion 的分子
+    sum: f64,
+    w_in: f64,
+    w_bound: f64,
+}
+
+// 一步操作后的适应度所需的统计量
+struct Summary {
+    size: f64,
+    sum: f64,
+    w_in: f64,
+    w_bound: f64,
+}
+
+impl Summary {
+    fn fitness(&self, fitness: Fitness) -> f64 {
+        if self.size == 0. {
+            return 0.;
+        }
+        match fitness {
+            Fitness::Cohesion => self.sum / (self.size * self.size),
+            Fitness::Cohesiveness(penalty) => {
+                let denom = self.w_in + self.w_bound + penalty * self.size;
+                if denom <= 0. {
+                    0.
+                } else {
+                    self.w_in / denom
+                }
+            }
+        }
+    }
+}
+
+impl<'a> ClusterState<'a> {
+    fn new(graph: &'a Graph, proteins: &[usize]) -> Self {
+        let mut state = Self {
+            graph,
+            members: BTreeSet::new(),
+            count: HashMap::new(),
+            weight: HashMap::new(),
+            sum: 0.,
+            w_in: 0.,
+            w_bound: 0.,
+        };
+        proteins.iter().for_each(|p| state.apply(*p, true));
+        state
+    }
+
+    fn fitness(&self, fitness: Fitness) -> f64 {
+        self.summary().fitness(fitness)
+    }
+
+    fn summary(&self) -> Summary {
+        Summary {
+            size: self.members.len() as f64,
+            sum: self.sum,
+            w_in: self.w_in,
+            w_bound: self.w_bound,
+        }
+    }
+
+    // 节点与簇内成员之间的连边
+    fn links(&self, node: usize) -> Vec<(usize, f64)> {
+        self.graph.nei_list[node]
+            .iter()
+            .filter(|(i, _)| **i != node && self.members.contains(i))
+            .map(|(i, w)| (*i, *w))
+            .collect()
+    }
+
+    // 加入或删除节点后的统计量
+    fn moved(&self, node: usize, add: bool) -> Summary {
+        let links = self.links(node);
+        let k = links.len() as f64;
+        let s = links.iter().map(|(_, w)| w).sum::<f64>();
+        let strength = self.graph.nei_list[node].values().sum::<f64>();
+        let size = self.members.len() as f64;
+        if add {
+            let delta = links
+                .iter()
+                .map(|(i, w)| self.weight[i] + w * (self.count[i] + 2.))
+                .sum::<f64>();
+            Summary {
+                size: size + 1.,
+                sum: self.sum + delta + s * (k + 1.),
+                w_in: self.w_in + s,
+                w_bound: self.w_bound - s + (strength - s),
+            }
+        } else {
+            let delta = links
+                .iter()
+                .map(|(i, w)| self.weight[i] + w * self.count[i])
+                .sum::<f64>();
+            Summary {
+                size: size - 1.,
+                sum: self.sum - self.weight[&node] * (self.count[&node] + 1.) - delta,
+                w_in: self.w_in - s,
+                w_bound: self.w_bound + s - (strength - s),
+            }
+        }
+    }
+
+    fn apply(&mut self, node: usize, add: bool) {
+        let summary = self.moved(node, add);
+        let links = self.links(node);
+        let sign = if add { 1. } else { -1. };
+        links.iter().for_each(|(i, w)| {
+            *self.count.get_mut(i).unwrap() += sign;
+            *self.weight.get_mut(i).unwrap() += sign * w;
+        });
+        if add {
+            self.members.insert(node);
+            self.count.insert(node, links.len() as f64);
+            self.weight
+                .insert(node, links.iter().map(|(_, w)| w).sum::<f64>());
+        } else {
+            self.members.remove(&node);
+            self.count.remove(&node);
+            self.weight.remove(&node);
+        }
+        self.sum = summary.sum;
+        self.w_in = summary.w_in;
+        self.w_bound = summary.w_bound;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+
+    use super::{ClusterState, Fitness, RefineOptions};
+
+    #[test]
+    fn test_refine_complex() {
+        // 0-1-2-3 为团，5 与 1、2、3 相连，4 与 0 以及团外的 6、7 相连
+        let g = Graph::new_from(vec![
+            (0, 1, 1.),
+            (0, 2, 1.),
+            (0, 3, 1.),
+            (1, 2, 1.),
+            (1, 3, 1.),
+            (2, 3, 1.),
+            (0, 4, 1.),
+            (1, 5, 1.),
+            (2, 5, 1.),
+            (3, 5, 1.),
+            (4, 6, 1.),
+            (4, 7, 1.),
+            (6, 7, 1.),
+        ]);
+
+        let options = RefineOptions {
+            fitness: Fitness::Cohesiveness(0.),
+            ..Default::default()
+        };
+        let refined = g.refine_complex(&[0, 1, 2, 4], &options);
+        assert_eq!(refined, vec![0, 1, 2, 3, 5]);
+
+        let options = RefineOptions {
+            max_size: 4,
+            ..options
+        };
+        let refined = g.refine_complex(&[0, 1, 2], &options);
+        assert_eq!(refined, vec![0, 1, 2, 3]);
+
+        // 默认的内聚力加入与团紧密相连的 5，且不降低适应度
+        let options = RefineOptions::default();
+        let refined = g.refine_complex(&[0, 1, 2, 3], &options);
+        assert_eq!(refined, vec![0, 1, 2, 3, 5]);
+        assert!(g.fitness(&refined, options.fitness) >= g.fitness(&[0, 1, 2, 3], options.fitness));
+    }
+
+    #[test]
+    fn test_incremental_fitness() {
+        let g = Graph::new_from_file("./data/collins/collins.txt", true);
+        let cluster = (0..15).collect::<Vec<_>>();
+        let state = ClusterState::new(&g, &cluster);
+        for fitness in [Fitness::Cohesion, Fitness::Cohesiveness(2.)] {
+            assert!((state.fitness(fitness) - g.fitness(&cluster, fitness)).abs() < 1e-9);
+            let removed = state.moved(3, false).fitness(fitness);
+            let expected = g.fitness(&[&cluster[..3], &cluster[4..]].concat(), fitness);
+            assert!((removed - expected).abs() < 1e-9);
+            let added = state.moved(20, true).fitness(fitness);
+            let expected = g.fitness(&[&cluster[..], &[20]].concat(), fitness);
+            assert!((added - expected).abs() < 1e-9);
+        }
+    }
+}
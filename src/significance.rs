@@ -0,0 +1,295 @@
+/// 复合物的统计显著性：内部连边数的超几何/二项检验，以及保持度分布的随机抽样检验
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    io::{self, BufWriter, Write},
+};
+
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use rayon::prelude::*;
+
+use crate::{
+    eva::Complex,
+    graph::Graph,
+    stats::{benjamini_hochberg, binomial_sf, hypergeometric_sf},
+    writer::create_file,
+};
+
+/// 默认的随机抽样次数
+pub const EMPIRICAL_SAMPLES: usize = 1000;
+/// 默认的显著性水平（校正后）
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+// 抽样时遇到已选节点的重试次数
+const SAMPLE_RETRIES: usize = 16;
+
+// 内部连边数的解析检验
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EdgeTest {
+    // 网络中所有节点对里有 |E| 条边，复合物内的节点对为不放回抽样
+    #[default]
+    Hypergeometric,
+    // 复合物内每个节点对以网络密度独立连边
+    Binomial,
+}
+
+#[derive(Debug, Clone)]
+pub struct SignificanceOptions {
+    pub test: EdgeTest,
+    // 保持度分布的随机抽样次数，为0时不计算经验 p 值
+    pub samples: usize,
+    pub seed: u64,
+    // Benjamini–Hochberg 校正后的显著性水平
+    pub alpha: f64,
+}
+
+impl Default for SignificanceOptions {
+    fn default() -> Self {
+        Self {
+            test: EdgeTest::default(),
+            samples: EMPIRICAL_SAMPLES,
+            seed: 0,
+            alpha: SIGNIFICANCE_LEVEL,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexSignificance {
+    // 在网络中的成员数和内部连边数
+    pub size: usize,
+    pub internal_edges: usize,
+    pub p_value: f64,
+    // 在整个预测集合上校正后的 q 值
+    pub q_value: f64,
+    pub empirical_p: Option<f64>,
+    pub empirical_q: Option<f64>,
+}
+
+impl ComplexSignificance {
+    // 解析检验和经验检验（若有）校正后都不大于 alpha
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.q_value <= alpha && self.empirical_q.is_none_or(|q| q <= alpha)
+    }
+}
+
+// 计算每个复合物的显著性，不在网络中的成员忽略
+pub fn complex_significance(
+    graph: &Graph,
+    complexes: &[Complex<String>],
+    options: &SignificanceOptions,
+) -> Vec<ComplexSignificance> {
+    let protein_id = graph
+        .id_protein
+        .iter()
+        .enumerate()
+        .map(|(id, p)| (p.as_str(), id))
+        .collect::<HashMap<_, _>>();
+    let n = graph.node_count as u64;
+    let pairs = n * n.saturating_sub(1) / 2;
+    let edges = graph
+        .nei_list
+        .iter()
+        .enumerate()
+        .map(|(a, nei)| nei.keys().filter(|b| **b > a).count())
+        .sum::<usize>() as u64;
+    let density = if pairs == 0 {
+        0.
+    } else {
+        edges as f64 / pairs as f64
+    };
+
+    // 按度分组，抽样时用相同度的节点替换成员
+    let mut by_degree = BTreeMap::<usize, Vec<usize>>::new();
+    for (node, nei) in graph.nei_list.iter().enumerate() {
+        by_degree.entry(nei.len()).or_default().push(node);
+    }
+
+    let mut result = complexes
+        .par_iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let nodes = c
+                .proteins
+                .iter()
+                .filter_map(|p| protein_id.get(p.as_str()).copied())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            let observed = internal_edges(graph, &nodes);
+            let k = nodes.len() as u64;
+            let draws = k * k.saturating_sub(1) / 2;
+            let p_value = match options.test {
+                EdgeTest::Hypergeometric => hypergeometric_sf(observed as u64, pairs, edges, draws),
+                EdgeTest::Binomial => binomial_sf(observed as u64, draws, density),
+            };
+
+            let empirical_p = (options.samples > 0).then(|| {
+                let mut rng =
+                    Xoshiro256PlusPlus::seed_from_u64(options.seed.wrapping_add(i as u64));
+                let hits = (0..options.samples)
+                    .filter(|_| {
+                        let sample = degree_preserving_sample(graph, &nodes, &by_degree, &mut rng);
+                        internal_edges(graph, &sample) >= observed
+                    })
+                    .count();
+                (hits + 1) as f64 / (options.samples + 1) as f64
+            });
+
+            ComplexSignificance {
+                size: nodes.len(),
+                internal_edges: observed,
+                p_value,
+                q_value: p_value,
+                empirical_p,
+                empirical_q: empirical_p,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let q = benjamini_hochberg(&result.iter().map(|s| s.p_value).collect::<Vec<_>>());
+    result.iter_mut().zip(q).for_each(|(s, q)| s.q_value = q);
+    if options.samples > 0 {
+        let empirical = result
+            .iter()
+            .map(|s| s.empirical_p.unwrap())
+            .collect::<Vec<_>>();
+        let q = benjamini_hochberg(&empirical);
+        result
+            .iter_mut()
+            .zip(q)
+            .for_each(|(s, q)| s.empirical_q = Some(q));
+    }
+    result
+}
+
+// 只保留显著的复合物，用于输出结果前的过滤
+pub fn filter_significant(
+    graph: &Graph,
+    complexes: Vec<Complex<String>>,
+    options: &SignificanceOptions,
+) -> Vec<Complex<String>> {
+    let significance = complex_significance(graph, &complexes, options);
+    complexes
+        .into_iter()
+        .zip(significance)
+        .filter(|(_, s)| s.is_significant(options.alpha))
+        .map(|(c, _)| c)
+        .collect()
+}
+
+// 每行一个复合物: complex_id, size, internal_edges, p_value, q_value, empirical_p, empirical_q
+pub fn write_significance(file: &str, significance: &[ComplexSignificance]) -> io::Result<()> {
+    let mut out = BufWriter::new(create_file(file)?);
+    writeln!(
+        out,
+        "complex_id\tsize\tinternal_edges\tp_value\tq_value\tempirical_p\tempirical_q"
+    )?;
+    let format = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.4e}", v));
+    for (id, s) in significance.iter().enumerate() {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{:.4e}\t{:.4e}\t{}\t{}",
+            id,
+            s.size,
+            s.internal_edges,
+            s.p_value,
+            s.q_value,
+            format(s.empirical_p),
+            format(s.empirical_q)
+        )?;
+    }
+    out.flush()
+}
+
+fn internal_edges(graph: &Graph, nodes: &[usize]) -> usize {
+    let mut count = 0;
+    for (i, a) in nodes.iter().enumerate() {
+        for b in nodes.iter().skip(i + 1) {
+            if graph.nei_list[*a].contains_key(b) {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+// 每个成员替换为一个度相同的随机节点（不重复），
+// 相同度的节点不够时从全部节点中抽取
+fn degree_preserving_sample(
+    graph: &Graph,
+    nodes: &[usize],
+    by_degree: &BTreeMap<usize, Vec<usize>>,
+    rng: &mut Xoshiro256PlusPlus,
+) -> Vec<usize> {
+    let mut sample = Vec::with_capacity(nodes.len());
+    let mut used = HashSet::new();
+    for node in nodes {
+        let candidates = &by_degree[&graph.nei_list[*node].len()];
+        // 复合物远小于候选集合，重复时重新抽取即可
+        let pick = (0..SAMPLE_RETRIES)
+            .map(|_| candidates[rng.random_range(0..candidates.len())])
+            .find(|c| !used.contains(c))
+            .or_else(|| candidates.iter().find(|c| !used.contains(*c)).copied())
+            .unwrap_or_else(|| loop {
+                let c = rng.random_range(0..graph.node_count);
+                if !used.contains(&c) {
+                    break c;
+                }
+            });
+        used.insert(pick);
+        sample.push(pick);
+    }
+    sample
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{eva::Complex, graph::Graph};
+
+    use super::{complex_significance, filter_significant, EdgeTest, SignificanceOptions};
+
+    #[test]
+    fn test_complex_significance() {
+        // 一个5-团，其余100个节点构成每个节点度为4的环
+        let mut edges = Vec::new();
+        for a in 0..5 {
+            for b in a + 1..5 {
+                edges.push((a, b, 1.));
+            }
+        }
+        for a in 0..100 {
+            edges.push((5 + a, 5 + (a + 1) % 100, 1.));
+            edges.push((5 + a, 5 + (a + 2) % 100, 1.));
+        }
+        let mut g = Graph::new_from(edges);
+        g.id_protein = (0..g.node_count).map(|i| format!("P{}", i)).collect();
+
+        let complex =
+            |ids: &[usize]| Complex::new(ids.iter().map(|i| format!("P{}", i)).collect(), 0.);
+        let complexes = vec![complex(&[0, 1, 2, 3, 4]), complex(&[20, 40, 60, 80])];
+        let options = SignificanceOptions {
+            samples: 200,
+            ..Default::default()
+        };
+        let significance = complex_significance(&g, &complexes, &options);
+        assert_eq!(significance[0].internal_edges, 10);
+        assert!(significance[0].p_value < 1e-6);
+        assert!(significance[0].is_significant(options.alpha));
+        assert_eq!(significance[1].internal_edges, 0);
+        assert_eq!(significance[1].p_value, 1.);
+        assert!(!significance[1].is_significant(options.alpha));
+
+        // 结果可复现
+        assert_eq!(significance, complex_significance(&g, &complexes, &options));
+
+        let binomial = SignificanceOptions {
+            test: EdgeTest::Binomial,
+            samples: 0,
+            ..Default::default()
+        };
+        let filtered = filter_significant(&g, complexes, &binomial);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].proteins[0], "P0");
+    }
+}
@@ -0,0 +1,129 @@
+/// 显著性检验中用到的分布函数、多重检验校正和百分位数
+use std::f64::consts::PI;
+
+// Lanczos 近似 (g = 7, n = 9)
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+// ln Γ(x)，x > 0
+pub fn ln_gamma(x: f64) -> f64 {
+    if x < 0.5 {
+        // 反射公式
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1. - x);
+    }
+    let x = x - 1.;
+    let t = x + 7.5;
+    let sum = LANCZOS
+        .iter()
+        .enumerate()
+        .skip(1)
+        .fold(LANCZOS[0], |acc, (i, c)| acc + c / (x + i as f64));
+    0.5 * (2. * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+// ln C(n, k)
+pub fn ln_choose(n: u64, k: u64) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+    ln_gamma(n as f64 + 1.) - ln_gamma(k as f64 + 1.) - ln_gamma((n - k) as f64 + 1.)
+}
+
+// 超几何分布的上尾概率 P(X >= k)：
+// 总体 population 中有 successes 个成功，不放回地抽取 draws 个
+pub fn hypergeometric_sf(k: u64, population: u64, successes: u64, draws: u64) -> f64 {
+    let lower = draws.saturating_sub(population - successes);
+    let upper = successes.min(draws);
+    if k <= lower {
+        return 1.;
+    }
+    if k > upper {
+        return 0.;
+    }
+    let total = ln_choose(population, draws);
+    let p = (k..=upper)
+        .map(|i| {
+            (ln_choose(successes, i) + ln_choose(population - successes, draws - i) - total).exp()
+        })
+        .sum::<f64>();
+    p.min(1.)
+}
+
+// 二项分布的上尾概率 P(X >= k)，X ~ B(n, p)
+pub fn binomial_sf(k: u64, n: u64, p: f64) -> f64 {
+    if k == 0 {
+        return 1.;
+    }
+    if k > n || p <= 0. {
+        return 0.;
+    }
+    if p >= 1. {
+        return 1.;
+    }
+    let p = (k..=n)
+        .map(|i| (ln_choose(n, i) + i as f64 * p.ln() + (n - i) as f64 * (1. - p).ln()).exp())
+        .sum::<f64>();
+    p.min(1.)
+}
+
+// Benjamini–Hochberg 校正，返回与输入顺序一致的 q 值
+pub fn benjamini_hochberg(p_values: &[f64]) -> Vec<f64> {
+    let n = p_values.len();
+    let mut order = (0..n).collect::<Vec<_>>();
+    order.sort_by(|a, b| p_values[*b].total_cmp(&p_values[*a]).then(a.cmp(b)));
+
+    // 从最大的 p 值开始取累积最小值，保证 q 值单调
+    let mut q = vec![0.; n];
+    let mut min = 1_f64;
+    for (r, i) in order.into_iter().enumerate() {
+        let rank = n - r;
+        min = min.min(p_values[i] * n as f64 / rank as f64);
+        q[i] = min;
+    }
+    q
+}
+
+// 最近秩法计算百分位数，values 为空时返回正无穷
+pub fn percentile(values: &[f64], p: f64) -> f64 {
+    if values.is_empty() {
+        return f64::INFINITY;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let rank = (p / 100. * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{benjamini_hochberg, binomial_sf, hypergeometric_sf, ln_choose};
+
+    #[test]
+    fn test_distributions() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(close(ln_choose(10, 3).exp(), 120.));
+        assert!(close(ln_choose(5, 0).exp(), 1.));
+        // 从 20 个中 5 个为成功的总体中抽 4 个，至少抽到 2 个成功
+        assert!(close(
+            hypergeometric_sf(2, 20, 5, 4),
+            0.248_710_010_319_917_44
+        ));
+        assert!(close(hypergeometric_sf(0, 20, 5, 4), 1.));
+        assert!(close(hypergeometric_sf(5, 20, 5, 4), 0.));
+        assert!(close(binomial_sf(2, 3, 0.5), 0.5));
+        assert!(close(binomial_sf(4, 3, 0.5), 0.));
+
+        let q = benjamini_hochberg(&[0.01, 0.04, 0.03, 0.5]);
+        let expected = [0.04, 0.04 * 4. / 3., 0.04 * 4. / 3., 0.5];
+        assert!(q.iter().zip(expected.iter()).all(|(a, b)| close(*a, *b)));
+    }
+}
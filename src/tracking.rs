@@ -0,0 +1,247 @@
+/// 跟踪复合物在相邻动态子网络之间的变化：形成、增长、缩小、分裂、合并和解散
+use std::io::{self, BufWriter, Write};
+
+use serde::Serialize;
+
+use crate::{eva::Complex, writer::create_file};
+
+/// 默认的匹配阈值（重叠分数）
+pub const TRACK_OVERLAP_SCORE: f64 = 0.5;
+
+#[derive(Debug, Clone)]
+pub struct TrackOptions {
+    // 相邻时间点的复合物重叠分数不小于该值时视为匹配
+    pub threshold: f64,
+}
+
+impl Default for TrackOptions {
+    fn default() -> Self {
+        Self {
+            threshold: TRACK_OVERLAP_SCORE,
+        }
+    }
+}
+
+// 复合物生命周期中的事件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    // 与上一时间点的复合物都不匹配
+    Form,
+    Grow { from: usize, to: usize },
+    Shrink { from: usize, to: usize },
+    // 从另一条轨迹分裂出来
+    Split { from: usize },
+    // 其他轨迹并入该轨迹
+    Merge { from: Vec<usize> },
+    // 与下一时间点的复合物都不匹配
+    Dissolve,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LifecycleEvent {
+    pub time_point: usize,
+    pub track: usize,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+// 一个复合物在各时间点的轨迹
+#[derive(Debug, Clone)]
+pub struct Track {
+    pub id: usize,
+    // 每个时间点对应的复合物下标，None 为不活跃
+    pub timeline: Vec<Option<usize>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tracking {
+    pub tracks: Vec<Track>,
+    pub events: Vec<LifecycleEvent>,
+}
+
+// per_time[t] 为第t个动态子网络识别出的复合物，按时间顺序排列
+// 相邻时间点之间按重叠分数从大到小贪心匹配，匹配上的复合物延续同一条轨迹
+pub fn track_complexes(per_time: &[Vec<Complex<String>>], options: &TrackOptions) -> Tracking {
+    let mut tracks = Vec::<Track>::new();
+    let mut events = Vec::new();
+    // 上一时间点的复合物所属的轨迹
+    let mut prev_track = Vec::<usize>::new();
+
+    for (t, complexes) in per_time.iter().enumerate() {
+        let prev = match t {
+            0 => &[][..],
+            _ => &per_time[t - 1][..],
+        };
+
+        let mut pairs = Vec::new();
+        for (i, p) in prev.iter().enumerate() {
+            for (j, c) in complexes.iter().enumerate() {
+                let os = p.overlap_score(c).1;
+                if os.ge(&options.threshold) {
+                    pairs.push((os, i, j));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| b.0.total_cmp(&a.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+
+        let mut continued = vec![false; prev.len()];
+        let mut cur_track = vec![None; complexes.len()];
+        for (_, i, j) in pairs.iter() {
+            if !continued[*i] && cur_track[*j].is_none() {
+                continued[*i] = true;
+                cur_track[*j] = Some(prev_track[*i]);
+            }
+        }
+
+        for (j, c) in complexes.iter().enumerate() {
+            // 按重叠分数从大到小排列的前驱
+            let preds = pairs
+                .iter()
+                .filter(|(_, _, k)| *k == j)
+                .map(|(_, i, _)| *i)
+                .collect::<Vec<_>>();
+            let mut push = |track: usize, event: Event| {
+                events.push(LifecycleEvent {
+                    time_point: t,
+                    track,
+                    event,
+                })
+            };
+
+            match cur_track[j] {
+                Some(track) => {
+                    let merged = preds
+                        .iter()
+                        .filter(|i| !continued[**i])
+                        .map(|i| prev_track[*i])
+                        .collect::<Vec<_>>();
+                    if !merged.is_empty() {
+                        push(track, Event::Merge { from: merged });
+                    }
+                    let i = tracks[track].timeline[t - 1].unwrap();
+                    let (from, to) = (prev[i].len(), c.len());
+                    if to > from {
+                        push(track, Event::Grow { from, to });
+                    } else if to < from {
+                        push(track, Event::Shrink { from, to });
+                    }
+                }
+                None => {
+                    let track = tracks.len();
+                    tracks.push(Track {
+                        id: track,
+                        timeline: vec![None; per_time.len()],
+                    });
+                    cur_track[j] = Some(track);
+                    match preds.first() {
+                        Some(i) => push(
+                            track,
+                            Event::Split {
+                                from: prev_track[*i],
+                            },
+                        ),
+                        None => push(track, Event::Form),
+                    }
+                }
+            }
+            tracks[cur_track[j].unwrap()].timeline[t] = Some(j);
+        }
+
+        // 没有延续也没有匹配的复合物在该时间点解散
+        for (i, _) in continued.iter().enumerate().filter(|(_, c)| !**c) {
+            if !pairs.iter().any(|(_, k, _)| *k == i) {
+                events.push(LifecycleEvent {
+                    time_point: t,
+                    track: prev_track[i],
+                    event: Event::Dissolve,
+                });
+            }
+        }
+
+        prev_track = cur_track.into_iter().map(|c| c.unwrap()).collect();
+    }
+
+    Tracking { tracks, events }
+}
+
+impl Tracking {
+    // 轨迹的活跃表：每行一条轨迹，每列为该时间点复合物的大小，0表示不活跃
+    // labels 为时间点的列名，为空时使用时间点下标
+    pub fn write_timeline(
+        &self,
+        file: &str,
+        per_time: &[Vec<Complex<String>>],
+        labels: &[String],
+    ) -> io::Result<()> {
+        let mut file = BufWriter::new(create_file(file)?);
+        let header = (0..per_time.len())
+            .map(|t| labels.get(t).cloned().unwrap_or(t.to_string()))
+            .collect::<Vec<_>>();
+        writeln!(file, "track\t{}", header.join("\t"))?;
+        for track in self.tracks.iter() {
+            let sizes = track
+                .timeline
+                .iter()
+                .enumerate()
+                .map(|(t, c)| c.map_or(0, |c| per_time[t][c].len()).to_string())
+                .collect::<Vec<_>>();
+            writeln!(file, "{}\t{}", track.id, sizes.join("\t"))?;
+        }
+        file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eva::Complex;
+
+    use super::{track_complexes, Event, TrackOptions};
+
+    fn complex(proteins: &[&str]) -> Complex<String> {
+        Complex::new(proteins.iter().map(|p| p.to_string()).collect(), 0.)
+    }
+
+    #[test]
+    fn test_track_complexes() {
+        let per_time = vec![
+            vec![complex(&["A", "B", "C", "D"]), complex(&["X", "Y", "Z"])],
+            vec![complex(&["A", "B", "C", "D", "E"])],
+            vec![complex(&["A", "B", "C"]), complex(&["C", "D", "E"])],
+            vec![complex(&["A", "B", "C", "D", "E"])],
+        ];
+        let tracking = track_complexes(&per_time, &TrackOptions::default());
+        assert_eq!(tracking.tracks.len(), 3);
+        assert_eq!(
+            tracking.tracks[0].timeline,
+            vec![Some(0), Some(0), Some(0), Some(0)]
+        );
+        assert_eq!(tracking.tracks[2].timeline, vec![None, None, Some(1), None]);
+
+        let events = tracking
+            .events
+            .iter()
+            .map(|e| (e.time_point, e.track, e.event.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                (0, 0, Event::Form),
+                (0, 1, Event::Form),
+                (1, 0, Event::Grow { from: 4, to: 5 }),
+                (1, 1, Event::Dissolve),
+                (2, 0, Event::Shrink { from: 5, to: 3 }),
+                (2, 2, Event::Split { from: 0 }),
+                (3, 0, Event::Merge { from: vec![2] }),
+                (3, 0, Event::Grow { from: 3, to: 5 }),
+            ]
+        );
+
+        let file = std::env::temp_dir().join(format!("timeline_{}.tsv", std::process::id()));
+        let file = file.to_str().unwrap();
+        tracking.write_timeline(file, &per_time, &[]).unwrap();
+        let table = std::fs::read_to_string(file).unwrap();
+        assert_eq!(table.lines().nth(1), Some("0\t4\t5\t3\t5"));
+        std::fs::remove_file(file).unwrap();
+    }
+}
@@ -0,0 +1,322 @@
+/// 将识别出的复合物以不同格式写入文件
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use serde::Serialize;
+
+use crate::eva::Complex;
+
+/// 默认的内聚力阈值，内聚力不大于该值的复合物不输出
+pub const DEFAULT_COHESION_CUTOFF: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    // 每行一个复合物，成员以制表符分隔（evaluation/match.py 的输入格式）
+    Membership,
+    // 每行一个成员: complex_id, protein, cohesion
+    Long,
+    Json,
+    // 每行一个复合物: complex_id, size, cohesion, proteins(以;分隔)
+    Csv,
+}
+
+impl OutputFormat {
+    // 根据文件后缀推断输出格式，无法识别时为None
+    pub fn from_path(path: &str) -> Option<Self> {
+        match Path::new(path).extension()?.to_str()? {
+            "txt" => Some(Self::Membership),
+            "tsv" => Some(Self::Long),
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    // 格式名或对应的文件后缀
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "membership" | "txt" => Ok(Self::Membership),
+            "long" | "tsv" => Ok(Self::Long),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!("unknown output format: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WriterOptions {
+    pub format: OutputFormat,
+    pub cohesion_cutoff: f64,
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        Self {
+            format: OutputFormat::Membership,
+            cohesion_cutoff: DEFAULT_COHESION_CUTOFF,
+        }
+    }
+}
+
+pub trait ComplexWriter {
+    // 写入一个复合物，id 为其在输出中的编号
+    fn write_complex(&mut self, id: usize, complex: &Complex<String>) -> io::Result<()>;
+    // 写入结尾并刷新缓冲区
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+pub struct MembershipWriter<W: Write> {
+    inner: W,
+}
+
+pub struct LongWriter<W: Write> {
+    inner: W,
+    header: bool,
+}
+
+pub struct JsonWriter<W: Write> {
+    inner: W,
+    count: usize,
+}
+
+pub struct CsvWriter<W: Write> {
+    inner: W,
+    header: bool,
+}
+
+impl<W: Write> MembershipWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+}
+
+impl<W: Write> LongWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            header: false,
+        }
+    }
+}
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner, count: 0 }
+    }
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            header: false,
+        }
+    }
+}
+
+impl<W: Write> ComplexWriter for MembershipWriter<W> {
+    fn write_complex(&mut self, _: usize, complex: &Complex<String>) -> io::Result<()> {
+        writeln!(self.inner, "{}", complex.proteins.join("\t"))
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> ComplexWriter for LongWriter<W> {
+    fn write_complex(&mut self, id: usize, complex: &Complex<String>) -> io::Result<()> {
+        if !self.header {
+            writeln!(self.inner, "complex_id\tprotein\tcohesion")?;
+            self.header = true;
+        }
+        for protein in complex.proteins.iter() {
+            writeln!(self.inner, "{}\t{}\t{:.4}", id, protein, complex.cohesion)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[derive(Serialize)]
+struct JsonComplex<'a> {
+    id: usize,
+    size: usize,
+    cohesion: f64,
+    proteins: &'a [String],
+}
+
+impl<W: Write> ComplexWriter for JsonWriter<W> {
+    fn write_complex(&mut self, id: usize, complex: &Complex<String>) -> io::Result<()> {
+        let record = JsonComplex {
+            id,
+            size: complex.len(),
+            cohesion: complex.cohesion,
+            proteins: &complex.proteins,
+        };
+        let sep = if self.count == 0 { "[\n" } else { ",\n" };
+        write!(self.inner, "{}  {}", sep, serde_json::to_string(&record)?)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.count {
+            0 => writeln!(self.inner, "[]")?,
+            _ => writeln!(self.inner, "\n]")?,
+        }
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> ComplexWriter for CsvWriter<W> {
+    fn write_complex(&mut self, id: usize, complex: &Complex<String>) -> io::Result<()> {
+        if !self.header {
+            writeln!(self.inner, "complex_id,size,cohesion,proteins")?;
+            self.header = true;
+        }
+        writeln!(
+            self.inner,
+            "{},{},{:.4},{}",
+            id,
+            complex.len(),
+            complex.cohesion,
+            csv_field(&complex.proteins.join(";"))
+        )
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// 含有逗号、引号或换行的字段需要加引号
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// 创建文件，所在目录不存在时一并创建
+pub fn create_file(path: &str) -> io::Result<File> {
+    if let Some(parent) = Path::new(path).parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    File::create(path)
+}
+
+pub fn new_writer<W: Write + 'static>(inner: W, format: OutputFormat) -> Box<dyn ComplexWriter> {
+    match format {
+        OutputFormat::Membership => Box::new(MembershipWriter::new(inner)),
+        OutputFormat::Long => Box::new(LongWriter::new(inner)),
+        OutputFormat::Json => Box::new(JsonWriter::new(inner)),
+        OutputFormat::Csv => Box::new(CsvWriter::new(inner)),
+    }
+}
+
+// 将内聚力大于阈值的复合物写入文件，返回写入的复合物数量
+pub fn write_complexes<'a, I>(
+    path: &str,
+    complexes: I,
+    options: &WriterOptions,
+) -> io::Result<usize>
+where
+    I: IntoIterator<Item = &'a Complex<String>>,
+{
+    let file = BufWriter::new(create_file(path)?);
+    let mut writer = new_writer(file, options.format);
+    let mut count = 0;
+    for complex in complexes {
+        if complex.cohesion.le(&options.cohesion_cutoff) {
+            continue;
+        }
+        writer.write_complex(count, complex)?;
+        count += 1;
+    }
+    writer.finish()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use crate::eva::Complex;
+
+    use super::{
+        new_writer, write_complexes, ComplexWriter, CsvWriter, JsonWriter, LongWriter,
+        OutputFormat, WriterOptions,
+    };
+
+    fn complexes() -> Vec<Complex<String>> {
+        vec![
+            Complex::new(vec!["A".to_string(), "B".to_string(), "C".to_string()], 0.5),
+            Complex::new(vec!["C".to_string(), "D".to_string(), "E".to_string()], 0.1),
+        ]
+    }
+
+    fn written<W: ComplexWriter>(mut writer: W) -> W {
+        complexes()
+            .iter()
+            .enumerate()
+            .for_each(|(i, c)| writer.write_complex(i, c).unwrap());
+        writer.finish().unwrap();
+        writer
+    }
+
+    #[test]
+    fn test_writer_formats() {
+        let long = written(LongWriter::new(Vec::new())).inner;
+        let long = String::from_utf8(long).unwrap();
+        assert_eq!(long.lines().count(), 7);
+        assert_eq!(long.lines().nth(1), Some("0\tA\t0.5000"));
+
+        let csv = written(CsvWriter::new(Vec::new())).inner;
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().nth(2), Some("1,3,0.1000,C;D;E"));
+
+        let json = written(JsonWriter::new(Vec::new())).inner;
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[1]["proteins"][2], "E");
+
+        let mut empty = new_writer(Vec::new(), OutputFormat::Json);
+        empty.finish().unwrap();
+
+        assert_eq!("long".parse(), Ok(OutputFormat::Long));
+        assert_eq!("TXT".parse(), Ok(OutputFormat::Membership));
+        assert!("xlsx".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_write_complexes() {
+        let dir = std::env::temp_dir().join(format!("pcegs_writer_{}", std::process::id()));
+        let path = dir.join("nested").join("result.txt");
+        let path = path.to_str().unwrap();
+        assert_eq!(
+            OutputFormat::from_path(path),
+            Some(OutputFormat::Membership)
+        );
+
+        let count = write_complexes(path, &complexes(), &WriterOptions::default()).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(read_to_string(path).unwrap(), "A\tB\tC\n");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}